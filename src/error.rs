@@ -37,4 +37,7 @@ pub enum InglError {
 
     #[err("A transaction with an unauthorized signer was attempted.")]
     NotAuthorized,
+
+    #[err("The buyer is not on the allowlist of this private listing.")]
+    BuyerNotAllowed,
 }
@@ -8,7 +8,8 @@ use solana_program::{
 
 use crate::state::{
     consts::{self, REGISTRY_STORAGE_SEED},
-    LogLevel, MediationShares, StoredSecondaryItem,
+    BuybackClause, DutchAuction, EnglishAuction, InstallmentPlan, Lease, LogLevel, MediationPanel,
+    MediationShares, PriceSchedule, SecondaryItemKind, StoredListingType, StoredSecondaryItem,
 };
 
 #[derive(BorshSerialize, BorshDeserialize)]
@@ -16,6 +17,7 @@ pub struct SecondaryItem {
     cost: u64,
     name: String,
     description: String,
+    kind: SecondaryItemKind,
 }
 impl SecondaryItem {
     pub fn to_stored(&self) -> StoredSecondaryItem {
@@ -23,7 +25,79 @@ impl SecondaryItem {
             cost: self.cost,
             name: self.name.clone(),
             description: self.description.clone(),
+            kind: self.kind,
             date_validated: None,
+            request_mediation_date: None,
+            mediation_shares: None,
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct InstallmentTerms {
+    pub down_payment: u64,
+    pub installment_count: u8,
+    pub installment_interval: u32,
+    pub grace_period: u32,
+}
+impl InstallmentTerms {
+    pub fn to_stored(&self) -> InstallmentPlan {
+        InstallmentPlan {
+            down_payment: self.down_payment,
+            installment_count: self.installment_count,
+            installment_interval: self.installment_interval,
+            grace_period: self.grace_period,
+            installments_paid: 0,
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub enum ListingType {
+    FixedPrice,
+    EnglishAuction {
+        min_bid_increment: u64,
+        end_date: u32,
+        reserve_price: Option<u64>,
+    },
+    DutchAuction {
+        floor_price: u64,
+        end_date: u32,
+        schedule: PriceSchedule,
+    },
+    Lease {
+        epochs: u64,
+    },
+}
+impl ListingType {
+    pub fn to_stored(&self, listing_date: u32) -> StoredListingType {
+        match self {
+            ListingType::FixedPrice => StoredListingType::FixedPrice,
+            ListingType::EnglishAuction {
+                min_bid_increment,
+                end_date,
+                reserve_price,
+            } => StoredListingType::EnglishAuction(EnglishAuction {
+                min_bid_increment: *min_bid_increment,
+                end_date: *end_date,
+                reserve_price: *reserve_price,
+                highest_bid: None,
+            }),
+            ListingType::DutchAuction {
+                floor_price,
+                end_date,
+                schedule,
+            } => StoredListingType::DutchAuction(DutchAuction {
+                floor_price: *floor_price,
+                start_date: listing_date,
+                end_date: *end_date,
+                schedule: *schedule,
+            }),
+            ListingType::Lease { epochs } => StoredListingType::Lease(Lease {
+                epochs: *epochs,
+                lessee: None,
+                start_epoch: 0,
+            }),
         }
     }
 }
@@ -33,32 +107,141 @@ pub enum InstructionEnum {
     List {
         log_level: LogLevel,
         authorized_withdrawer_cost: u64,
+        listing_type: ListingType,
         mediatable_date: u32,
         secondary_items: Vec<SecondaryItem>,
         description: String,
         validator_name: String,
         validator_logo_url: String,
+        validator_info: Option<Pubkey>,
+        expires_at: Option<u32>,
+        allowed_buyers: Vec<Pubkey>,
+        payment_mint: Option<Pubkey>,
+        installment_terms: Option<InstallmentTerms>,
+        revenue_share_basis_points: Option<u16>,
+        buyback_clause: Option<BuybackClause>,
+        self_stake_accounts: u8,
+        delivery_interval: Option<u32>,
+        silence_interval: Option<u32>,
+        mediation_panel: Option<MediationPanel>,
     },
     Delist {
         log_level: LogLevel,
     },
+    DelistExpired {
+        log_level: LogLevel,
+    },
     Buy {
         log_level: LogLevel,
+        authorized_voter: Option<Pubkey>,
+        max_price: u64,
     },
     WithdrawRewards {
         log_level: LogLevel,
     },
     RequestMediation {
         log_level: LogLevel,
+        item_index: Option<u32>,
     },
     Mediate {
         log_level: LogLevel,
         mediation_shares: MediationShares,
+        item_index: Option<u32>,
     },
     ValidateSecondaryItemsTransfers {
         log_level: LogLevel,
         item_index: u32,
     },
+    PlaceBid {
+        log_level: LogLevel,
+        amount: u64,
+    },
+    SettleAuction {
+        log_level: LogLevel,
+    },
+    MakeOffer {
+        log_level: LogLevel,
+        amount: u64,
+        expiry_date: u32,
+    },
+    CancelOffer {
+        log_level: LogLevel,
+    },
+    AcceptOffer {
+        log_level: LogLevel,
+    },
+    UpdateListing {
+        log_level: LogLevel,
+        authorized_withdrawer_cost: Option<u64>,
+        mediation_interval: Option<u32>,
+        secondary_items: Option<Vec<SecondaryItem>>,
+        description: Option<String>,
+        validator_name: Option<String>,
+        validator_logo_url: Option<String>,
+        allowed_buyers: Option<Vec<Pubkey>>,
+    },
+    PayInstallment {
+        log_level: LogLevel,
+    },
+    ForfeitInstallments {
+        log_level: LogLevel,
+    },
+    BuyRevenueShare {
+        log_level: LogLevel,
+        basis_points: u16,
+    },
+    StartLease {
+        log_level: LogLevel,
+    },
+    EndLease {
+        log_level: LogLevel,
+    },
+    ExerciseBuyback {
+        log_level: LogLevel,
+    },
+    ExpireBuyback {
+        log_level: LogLevel,
+    },
+    UpdateCommission {
+        log_level: LogLevel,
+        commission: u8,
+    },
+    RotateValidatorIdentity {
+        log_level: LogLevel,
+    },
+    Claim {
+        log_level: LogLevel,
+    },
+    RefundUndeliveredItems {
+        log_level: LogLevel,
+    },
+    Finalize {
+        log_level: LogLevel,
+    },
+    AddMediator {
+        log_level: LogLevel,
+        mediator: Pubkey,
+        name: String,
+        contact: String,
+    },
+    RemoveMediator {
+        log_level: LogLevel,
+        mediator: Pubkey,
+    },
+    SuspendMediator {
+        log_level: LogLevel,
+        mediator: Pubkey,
+        suspended: bool,
+    },
+    VoteMediation {
+        log_level: LogLevel,
+        mediation_shares: MediationShares,
+        item_index: Option<u32>,
+    },
+    SettleMediation {
+        log_level: LogLevel,
+        item_index: Option<u32>,
+    },
 }
 impl InstructionEnum {
     pub fn decode(data: &[u8]) -> Self {
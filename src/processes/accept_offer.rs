@@ -0,0 +1,157 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    error::InglError,
+    log,
+    processes::{
+        buy::{
+            change_authorized_voter, change_authorized_withdrawer, hands_over_withdrawer,
+            log_authorized_voter_change, record_purchase, sale_proceeds_split,
+        },
+        cancel_offer::close_offer,
+    },
+    state::{
+        consts::{ESCROW_ACCOUNT_SEED, OFFER_SEED, PROGRAM_STORAGE_SEED, TEAM_ADDRESS},
+        LogLevel, Offer, Purchase, Storage, StoredListingType,
+    },
+    utils::{get_clock_data_from_account, move_lamports, AccountInfoHelpers, ResultExt},
+};
+
+/// Sells the validator to an offer's buyer at the offered price, through the same fee split
+/// and withdrawer handover as `buy_validator`.
+pub fn accept_offer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: LogLevel,
+) -> ProgramResult {
+    log!(log_level, 4, "accept_offer called");
+    let account_info_iter = &mut accounts.iter();
+    let authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    let storage_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let offer_account_info = next_account_info(account_info_iter)?;
+    let buyer_account_info = next_account_info(account_info_iter)?;
+    let pda_authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    let escrow_account_info = next_account_info(account_info_iter)?;
+    let team_account_info = next_account_info(account_info_iter)?;
+    let sysvar_clock_account_info = next_account_info(account_info_iter)?;
+
+    let clock_data =
+        get_clock_data_from_account(sysvar_clock_account_info).error_log("Error @ clock parse")?;
+
+    authorized_withdrawer_info
+        .assert_signer()
+        .error_log("Error @ authorized_withdrawer_info.assert_signer")?;
+    storage_account_info
+        .assert_seed(
+            program_id,
+            &[PROGRAM_STORAGE_SEED, vote_account_info.key.as_ref()],
+        )
+        .error_log("Error @ storage_account_info.assert_seed")?;
+    offer_account_info
+        .assert_seed(
+            program_id,
+            &[
+                OFFER_SEED,
+                vote_account_info.key.as_ref(),
+                buyer_account_info.key.as_ref(),
+            ],
+        )
+        .error_log("Error @ offer_account_info.assert_seed")?;
+    team_account_info
+        .assert_key_match(&TEAM_ADDRESS)
+        .error_log("Error @ team_account_info.assert_key_match")?;
+
+    let mut storage_data = Storage::parse(storage_account_info, program_id)?;
+    escrow_account_info
+        .assert_seed(
+            program_id,
+            &[
+                ESCROW_ACCOUNT_SEED,
+                vote_account_info.key.as_ref(),
+                &Purchase::escrow_seed(storage_data.date_listed),
+            ],
+        )
+        .error_log("Error @ escrow_account_info.assert_seed")?;
+    let offer_data = Offer::parse(offer_account_info, program_id)?;
+
+    authorized_withdrawer_info
+        .assert_key_match(&storage_data.authorized_withdrawer)
+        .error_log("Only the seller can accept an offer")?;
+    buyer_account_info
+        .assert_key_match(&offer_data.buyer)
+        .error_log("Error @ buyer_account_info.assert_key_match")?;
+
+    if let Some(_purchase) = storage_data.purchase {
+        Err(InglError::TooLate.utilize("validator is already bought"))?
+    }
+    if storage_data.is_expired(clock_data.unix_timestamp as u32) {
+        Err(InglError::TooLate.utilize("listing has expired"))?
+    }
+    storage_data
+        .assert_allowed_buyer(buyer_account_info.key)
+        .error_log("Error @ storage_data.assert_allowed_buyer")?;
+    if !matches!(storage_data.listing_type, StoredListingType::FixedPrice) {
+        Err(InglError::InvalidData.utilize("offers can only be accepted on fixed price listings"))?
+    }
+    if clock_data.unix_timestamp as u32 >= offer_data.expiry_date {
+        Err(InglError::TooLate.utilize("offer has expired"))?
+    }
+
+    if Some(offer_data.collateral) != storage_data.secondary_items_cost().checked_mul(2) {
+        Err(InglError::InvalidData.utilize("secondary items changed since the offer was made"))?
+    }
+
+    let (to_owner, to_escrow, to_team) = sale_proceeds_split(&storage_data, offer_data.amount)?;
+
+    log!(log_level, 2, "accept_offer: paying out offer");
+    move_lamports(offer_account_info, authorized_withdrawer_info, to_owner)
+        .error_log("Error @ transfer to owner")?;
+    move_lamports(offer_account_info, escrow_account_info, to_escrow)
+        .error_log("Error @ transfer to escrow")?;
+    move_lamports(offer_account_info, team_account_info, to_team)
+        .error_log("Error @ transfer to team")?;
+    close_offer(offer_account_info, buyer_account_info)?;
+
+    record_purchase(
+        &mut storage_data,
+        buyer_account_info.key,
+        buyer_account_info.key,
+        to_escrow,
+        &clock_data,
+    );
+    storage_data
+        .serialize(&mut &mut storage_account_info.data.borrow_mut()[..])
+        .error_log("Error @ storage_data.serialize")?;
+
+    if hands_over_withdrawer(&storage_data) {
+        log_authorized_voter_change(&storage_data, vote_account_info, &clock_data, log_level);
+        log!(log_level, 2, "accept_offer: change_authorized_voter");
+        change_authorized_voter(
+            program_id,
+            vote_account_info,
+            buyer_account_info.key,
+            pda_authorized_withdrawer_info,
+            sysvar_clock_account_info,
+        )
+        .error_log("Error @ change_authorized_voter")?;
+
+        log!(log_level, 2, "accept_offer: change_authorized_withdrawer");
+        change_authorized_withdrawer(
+            program_id,
+            vote_account_info,
+            buyer_account_info,
+            pda_authorized_withdrawer_info,
+            sysvar_clock_account_info,
+            log_level,
+        )
+        .error_log("Error @ change_authorized_withdrawer")?;
+    }
+
+    Ok(())
+}
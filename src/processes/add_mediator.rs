@@ -0,0 +1,137 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+
+use crate::{
+    error::InglError,
+    log,
+    state::{
+        consts::{
+            MAX_MEDIATORS, MEDIATOR_REGISTRY_SEED, MEDIATOR_REGISTRY_VALIDATION_PHRASE,
+            TEAM_ADDRESS,
+        },
+        LogLevel, Mediator, MediatorRegistry,
+    },
+    utils::{
+        create_pda_account, get_clock_data_from_account, move_lamports, AccountInfoHelpers,
+        ResultExt,
+    },
+};
+
+/// Registers a mediator along with their metadata. The team authority signs and pays the rent of
+/// the mediator registry, which is created on the first call.
+pub fn add_mediator(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: LogLevel,
+    mediator: Pubkey,
+    name: String,
+    contact: String,
+) -> ProgramResult {
+    log!(log_level, 4, "add_mediator called");
+    let account_info_iter = &mut accounts.iter();
+    let team_account_info = next_account_info(account_info_iter)?;
+    let registry_account_info = next_account_info(account_info_iter)?;
+    let sysvar_clock_account_info = next_account_info(account_info_iter)?;
+
+    let clock_data =
+        get_clock_data_from_account(sysvar_clock_account_info).error_log("Error @ clock parse")?;
+
+    team_account_info
+        .assert_signer()
+        .error_log("Error @ team_account_info.assert_signer")?;
+    team_account_info
+        .assert_key_match(&TEAM_ADDRESS)
+        .error_log("Only the team can manage mediators")?;
+    let (_registry_key, registry_bump) = registry_account_info
+        .assert_seed(program_id, &[MEDIATOR_REGISTRY_SEED])
+        .error_log("Error @ registry_account_info.assert_seed")?;
+
+    let mediator_data = Mediator {
+        key: mediator,
+        name,
+        contact,
+        date_added: clock_data.unix_timestamp as u32,
+        suspended: false,
+    };
+
+    if registry_account_info.data_is_empty() {
+        log!(log_level, 2, "add_mediator: creating mediator registry");
+        let registry_data = MediatorRegistry {
+            validation_phrase: MEDIATOR_REGISTRY_VALIDATION_PHRASE,
+            mediators: vec![mediator_data],
+        };
+        create_pda_account(
+            program_id,
+            team_account_info,
+            registry_account_info,
+            registry_data.get_space(),
+            &[MEDIATOR_REGISTRY_SEED, &[registry_bump]],
+        )
+        .error_log("Error @ create_pda_account")?;
+        registry_data
+            .serialize(&mut &mut registry_account_info.data.borrow_mut()[..])
+            .error_log("Error @ registry_data.serialize")?;
+        return Ok(());
+    }
+
+    let mut registry_data = MediatorRegistry::parse(registry_account_info, program_id)?;
+    if registry_data
+        .mediators
+        .iter()
+        .any(|registered| registered.key == mediator)
+    {
+        Err(InglError::InvalidData.utilize("mediator is already registered"))?
+    }
+    if registry_data.mediators.len() >= MAX_MEDIATORS {
+        Err(InglError::BeyondBounds.utilize("mediator registry is full"))?
+    }
+    registry_data.mediators.push(mediator_data);
+
+    store_mediator_registry(team_account_info, registry_account_info, &registry_data)
+}
+
+/// Resizes the mediator registry to fit `registry_data` and stores it. The team tops up or
+/// receives back the rent difference.
+pub fn store_mediator_registry<'a>(
+    team_account: &AccountInfo<'a>,
+    registry_account: &AccountInfo<'a>,
+    registry_data: &MediatorRegistry,
+) -> ProgramResult {
+    let space = registry_data.get_space();
+    let required_lamports = Rent::get()?.minimum_balance(space);
+    let current_lamports = registry_account.lamports();
+    if required_lamports > current_lamports {
+        invoke(
+            &system_instruction::transfer(
+                team_account.key,
+                registry_account.key,
+                required_lamports - current_lamports,
+            ),
+            &[team_account.clone(), registry_account.clone()],
+        )
+        .error_log("Error @ registry rent top up")?;
+    } else if current_lamports > required_lamports {
+        move_lamports(
+            registry_account,
+            team_account,
+            current_lamports - required_lamports,
+        )
+        .error_log("Error @ registry rent refund")?;
+    }
+
+    registry_account
+        .realloc(space, false)
+        .error_log("Error @ registry_account.realloc")?;
+    registry_data
+        .serialize(&mut &mut registry_account.data.borrow_mut()[..])
+        .error_log("Error @ registry_data.serialize")?;
+    Ok(())
+}
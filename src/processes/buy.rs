@@ -3,27 +3,40 @@ use solana_program::{
     account_info::{next_account_info, AccountInfo},
     clock::Clock,
     entrypoint::ProgramResult,
-    program::{invoke, invoke_signed},
+    program::invoke_signed,
+    program_error::ProgramError,
     pubkey::Pubkey,
+    stake::{self, state::StakeAuthorize},
     system_instruction, sysvar,
     vote::{self, instruction::authorize, state::VoteAuthorize},
 };
 
 use crate::{
     error::InglError,
+    log,
     state::{
         consts::{
-            ESCROWED_BASIS_POINTS, ESCROW_ACCOUNT_SEED, PDA_AUTHORIZED_WITHDRAWER_SEED,
-            PROGRAM_STORAGE_SEED, TEAM_ADDRESS, TEAM_FEES_BASIS_POINTS,
+            BUYBACK_SEED, BUYBACK_VALIDATION_PHRASE, ESCROWED_BASIS_POINTS, ESCROW_ACCOUNT_SEED,
+            PDA_AUTHORIZED_WITHDRAWER_SEED, PROGRAM_STORAGE_SEED, TEAM_ADDRESS,
+            TEAM_FEES_BASIS_POINTS,
         },
-        LogLevel, Purchase, Storage,
+        AuthorizedVoters, Buyback, BuybackClause, LogLevel, Purchase, SelfStake, Storage,
+        StoredListingType,
+    },
+    utils::{
+        assert_escrow_token_account, assert_token_account, assert_token_program_and_mint,
+        create_escrow_token_account, create_pda_account, get_clock_data_from_account,
+        get_inverse_transfer_fee, transfer_tokens, AccountInfoHelpers, OptionExt, ResultExt,
     },
-    utils::{get_clock_data_from_account, AccountInfoHelpers, OptionExt, ResultExt},
 };
 
+/// Buys a listing, for at most `max_price`. The vote accounts are assigned `authorized_voter`, or
+/// the buyer when `None`, along with the authorized withdrawer.
 pub fn buy_validator(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
+    authorized_voter: Option<Pubkey>,
+    max_price: u64,
     log_level: LogLevel,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
@@ -35,31 +48,80 @@ pub fn buy_validator(
     let pda_authorized_withdrawer_info = next_account_info(account_info_iter)?;
     let escrow_account_info = next_account_info(account_info_iter)?;
     let team_account_info = next_account_info(account_info_iter)?;
+    // Bundles first take their other vote accounts, in the order they are recorded, followed
+    // by the self-stake accounts of the listing. Token priced listings then take the token
+    // program, the payment mint, the payer, seller, escrow and team token accounts, the system
+    // program and the associated token program, in that order. Listings with a buyback clause
+    // take the buyback account instead.
+    let trailing_accounts = account_info_iter.as_slice();
 
     let clock_data =
         get_clock_data_from_account(sysvar_clock_account_info).error_log("Error @ clock parse")?;
 
-    verify_transfer_cost_and_edit_storage(
+    let storage_data = verify_transfer_cost_and_edit_storage(
         program_id,
         payer_account_info,
         storage_account_info,
         registered_authorized_withdrawer_info,
+        vote_account_info,
         escrow_account_info,
         team_account_info,
+        trailing_accounts,
+        &authorized_voter.unwrap_or(*payer_account_info.key),
+        max_price,
         &clock_data,
         log_level,
     )
     .error_log("Error @ verify_transfer_cost_and_edit_storage")?;
 
-    change_authorized_withdrawer(
-        program_id,
-        vote_account_info,
-        payer_account_info,
-        pda_authorized_withdrawer_info,
-        sysvar_clock_account_info,
-        log_level,
-    )
-    .error_log("Error @ change_authorized_withdrawer")?;
+    let (bundled_vote_account_infos, self_stake_account_infos) = trailing_accounts
+        [..storage_data.bundled_vote_accounts.len() + storage_data.self_stake.len()]
+        .split_at(storage_data.bundled_vote_accounts.len());
+    let purchase = storage_data
+        .purchase
+        .error_log("Error @ purchase was not recorded")?;
+    // Under a buyback call option, the buyer operates the validator while the program keeps
+    // the withdrawer.
+    if hands_over_withdrawer(&storage_data)
+        || matches!(
+            storage_data.buyback_clause,
+            Some(BuybackClause::CallOption { .. })
+        )
+    {
+        for vote_account in std::iter::once(vote_account_info).chain(bundled_vote_account_infos) {
+            log_authorized_voter_change(&storage_data, vote_account, &clock_data, log_level);
+            change_authorized_voter(
+                program_id,
+                vote_account,
+                &purchase.authorized_voter,
+                pda_authorized_withdrawer_info,
+                sysvar_clock_account_info,
+            )
+            .error_log("Error @ change_authorized_voter")?;
+        }
+    }
+    if hands_over_withdrawer(&storage_data) {
+        for vote_account in std::iter::once(vote_account_info).chain(bundled_vote_account_infos) {
+            change_authorized_withdrawer(
+                program_id,
+                vote_account,
+                payer_account_info,
+                pda_authorized_withdrawer_info,
+                sysvar_clock_account_info,
+                log_level,
+            )
+            .error_log("Error @ change_authorized_withdrawer")?;
+        }
+        change_stake_authorities(
+            program_id,
+            &storage_data.self_stake,
+            self_stake_account_infos,
+            payer_account_info.key,
+            pda_authorized_withdrawer_info,
+            sysvar_clock_account_info,
+        )
+        .error_log("Error @ change_stake_authorities")?;
+    }
 
     Ok(())
 }
@@ -69,21 +131,55 @@ pub fn verify_transfer_cost_and_edit_storage<'a>(
     payer_account: &AccountInfo<'a>,
     storage_account: &AccountInfo<'a>,
     registered_authorized_withdrawer: &AccountInfo<'a>,
+    vote_account: &AccountInfo<'a>,
     escrow_account: &AccountInfo<'a>,
     team_account: &AccountInfo<'a>,
+    trailing_accounts: &[AccountInfo<'a>],
+    authorized_voter: &Pubkey,
+    max_price: u64,
     clock_data: &Clock,
-    _log_level: LogLevel,
-) -> ProgramResult {
+    log_level: LogLevel,
+) -> Result<Storage, ProgramError> {
     storage_account
-        .assert_seed(program_id, &[PROGRAM_STORAGE_SEED])
+        .assert_seed(
+            program_id,
+            &[PROGRAM_STORAGE_SEED, vote_account.key.as_ref()],
+        )
         .error_log("Error @ storage pda validation")?;
     let mut storage_data =
         Storage::parse(storage_account, program_id).error_log("Error @ storage parse")?;
     registered_authorized_withdrawer
         .assert_key_match(&storage_data.authorized_withdrawer)
         .error_log("Error @ registered_authorized_withdrawer match")?;
+    vote_account
+        .assert_key_match(&storage_data.vote_account)
+        .error_log("Error @ vote_account match")?;
+    if trailing_accounts.len()
+        < storage_data.bundled_vote_accounts.len() + storage_data.self_stake.len()
+    {
+        Err(InglError::InvalidData
+            .utilize("Error @ bundled vote accounts or self-stake accounts are missing"))?
+    }
+    let (bundled_vote_account_infos, token_payment_accounts) =
+        trailing_accounts.split_at(storage_data.bundled_vote_accounts.len());
+    let token_payment_accounts = &token_payment_accounts[storage_data.self_stake.len()..];
+    for (bundled_vote_account_info, bundled_vote_account) in bundled_vote_account_infos
+        .iter()
+        .zip(&storage_data.bundled_vote_accounts)
+    {
+        bundled_vote_account_info
+            .assert_key_match(bundled_vote_account)
+            .error_log("Error @ bundled vote_account match")?;
+    }
     escrow_account
-        .assert_seed(program_id, &[ESCROW_ACCOUNT_SEED])
+        .assert_seed(
+            program_id,
+            &[
+                ESCROW_ACCOUNT_SEED,
+                vote_account.key.as_ref(),
+                &Purchase::escrow_seed(storage_data.date_listed),
+            ],
+        )
         .error_log("Error @ escrow pda validation")?;
 
     team_account
@@ -93,14 +189,158 @@ pub fn verify_transfer_cost_and_edit_storage<'a>(
     if let Some(_purchase) = storage_data.purchase {
         Err(InglError::TooLate.utilize("Error @ validator is already bought"))?
     }
+    if storage_data.revenue_share.is_some() {
+        Err(InglError::InvalidData.utilize("Error @ listing only sells revenue shares"))?
+    }
+    if storage_data.is_expired(clock_data.unix_timestamp as u32) {
+        Err(InglError::TooLate.utilize("listing has expired"))?
+    }
+    storage_data
+        .assert_allowed_buyer(payer_account.key)
+        .error_log("Error @ storage_data.assert_allowed_buyer")?;
+    storage_data
+        .assert_first_refusal(payer_account.key, clock_data.unix_timestamp as u32)
+        .error_log("Error @ storage_data.assert_first_refusal")?;
 
-    let secondary_item_cost = storage_data
-        .secondary_items
-        .iter()
-        .map(|item| item.cost)
-        .sum::<u64>();
+    let mut price = match &storage_data.listing_type {
+        StoredListingType::FixedPrice => storage_data.authorized_withdrawer_cost,
+        StoredListingType::DutchAuction(auction) => auction.current_price(
+            storage_data.authorized_withdrawer_cost,
+            clock_data.unix_timestamp as u32,
+        ),
+        _ => Err(InglError::InvalidData.utilize("Error @ listing can't be bought directly"))?,
+    };
+    // The seller may raise the price with `UpdateListing` while the purchase is pending, and a
+    // Dutch auction price depends on when the purchase lands.
+    if price > max_price {
+        Err(InglError::BeyondBounds.utilize("price is above the buyer's max_price"))?
+    }
+    if let Some(installment_plan) = &storage_data.installment_plan {
+        price = installment_plan.down_payment;
+    }
+    log!(
+        log_level,
+        3,
+        "price: {}, self-stake value: {}",
+        price,
+        storage_data.self_stake_value()
+    );
+    match storage_data.payment_mint {
+        None => transfer_sale_proceeds(
+            payer_account,
+            &[],
+            &storage_data,
+            price,
+            registered_authorized_withdrawer,
+            escrow_account,
+            team_account,
+        )
+        .error_log("Error @ transfer_sale_proceeds")?,
+        Some(payment_mint) => transfer_sale_proceeds_in_tokens(
+            payer_account,
+            token_payment_accounts,
+            &storage_data,
+            &payment_mint,
+            price,
+            escrow_account,
+        )
+        .error_log("Error @ transfer_sale_proceeds_in_tokens")?,
+    }
+
+    let (_to_owner, to_escrow, _to_team) = sale_proceeds_split(&storage_data, price)?;
+    record_purchase(
+        &mut storage_data,
+        payer_account.key,
+        authorized_voter,
+        to_escrow,
+        clock_data,
+    );
+
+    if let Some(buyback_clause) = storage_data.buyback_clause {
+        let buyback_account = token_payment_accounts
+            .first()
+            .error_log("Error @ buyback account is missing")?;
+        record_buyback(
+            program_id,
+            buyback_account,
+            payer_account,
+            vote_account,
+            Buyback {
+                validation_phrase: BUYBACK_VALIDATION_PHRASE,
+                vote_account: *vote_account.key,
+                seller: storage_data.authorized_withdrawer,
+                buyer: *payer_account.key,
+                clause: buyback_clause,
+                price,
+                date: clock_data.unix_timestamp as u32,
+            },
+        )
+        .error_log("Error @ record_buyback")?;
+    }
+
+    storage_data
+        .serialize(&mut &mut storage_account.data.borrow_mut()[..])
+        .error_log("Error @ storage serialize")?;
+    Ok(storage_data)
+}
 
-    let to_owner: u64 = (storage_data.authorized_withdrawer_cost as u128)
+/// On installment purchases, the withdrawer is handed over with the last installment, and
+/// under a buyback call option once the option expires. While a sold validator identity is not
+/// rotated yet, the program keeps the withdrawer to sign the rotation.
+pub fn hands_over_withdrawer(storage_data: &Storage) -> bool {
+    if storage_data.has_pending_identity_rotation() {
+        return false;
+    }
+    match (&storage_data.installment_plan, &storage_data.buyback_clause) {
+        (Some(installment_plan), _) => installment_plan.is_paid_in_full(),
+        (None, Some(BuybackClause::CallOption { .. })) => false,
+        (None, _) => true,
+    }
+}
+
+/// Records the buyback clause of a purchase in the buyback account of the vote account, paid for
+/// by the buyer. An account left over from an earlier sale is overwritten.
+pub fn record_buyback<'a>(
+    program_id: &Pubkey,
+    buyback_account: &AccountInfo<'a>,
+    payer_account: &AccountInfo<'a>,
+    vote_account: &AccountInfo<'a>,
+    buyback_data: Buyback,
+) -> ProgramResult {
+    let (_buyback_key, buyback_bump) = buyback_account
+        .assert_seed(program_id, &[BUYBACK_SEED, vote_account.key.as_ref()])
+        .error_log("Error @ buyback_account.assert_seed")?;
+
+    if buyback_account.data_is_empty() {
+        create_pda_account(
+            program_id,
+            payer_account,
+            buyback_account,
+            Buyback::get_space(),
+            &[BUYBACK_SEED, vote_account.key.as_ref(), &[buyback_bump]],
+        )
+        .error_log("Error @ create_pda_account")?;
+    } else {
+        buyback_account
+            .assert_owner(program_id)
+            .error_log("Error @ buyback_account.assert_owner")?;
+    }
+
+    buyback_data
+        .serialize(&mut &mut buyback_account.data.borrow_mut()[..])
+        .error_log("Error @ buyback_data.serialize")?;
+    Ok(())
+}
+
+/// Returns the seller, escrow and team shares of a sale at `price`. The escrow share includes
+/// twice the secondary items cost.
+pub fn sale_proceeds_split(
+    storage_data: &Storage,
+    price: u64,
+) -> Result<(u64, u64, u64), ProgramError> {
+    let secondary_item_cost = storage_data.secondary_items_cost();
+
+    let to_owner: u64 = (price as u128)
         .checked_mul(
             (10000
                 - (if storage_data.secondary_items.len() > 0 {
@@ -115,7 +355,7 @@ pub fn verify_transfer_cost_and_edit_storage<'a>(
         .checked_div(10000)
         .error_log("to_owner div calculation error")? as u64;
     let to_escrow: u64 = secondary_item_cost * 2
-        + (storage_data.authorized_withdrawer_cost as u128)
+        + (price as u128)
             .checked_mul(if storage_data.secondary_items.len() > 0 {
                 ESCROWED_BASIS_POINTS.into()
             } else {
@@ -124,60 +364,168 @@ pub fn verify_transfer_cost_and_edit_storage<'a>(
             .error_log("to_escrow mul calculation error")?
             .checked_div(10000)
             .error_log("to_escrow div calculation error")? as u64;
-    let to_team: u64 = (storage_data.authorized_withdrawer_cost as u128)
+    let to_team: u64 = (price as u128)
         .checked_mul(TEAM_FEES_BASIS_POINTS.into())
         .error_log("to_team mul calculation error")?
         .checked_div(10000)
         .error_log("to_team div calculation error")? as u64;
 
+    Ok((to_owner, to_escrow, to_team))
+}
+
+/// Pays out a sale at `price` from a system owned account using `sale_proceeds_split`.
+/// `source_seeds` must sign for `source_account` when it is a PDA.
+pub fn transfer_sale_proceeds<'a>(
+    source_account: &AccountInfo<'a>,
+    source_seeds: &[&[&[u8]]],
+    storage_data: &Storage,
+    price: u64,
+    registered_authorized_withdrawer: &AccountInfo<'a>,
+    escrow_account: &AccountInfo<'a>,
+    team_account: &AccountInfo<'a>,
+) -> ProgramResult {
+    let (to_owner, to_escrow, to_team) = sale_proceeds_split(storage_data, price)?;
+
     let do_transfers = || -> ProgramResult {
-        invoke(
+        invoke_signed(
             &system_instruction::transfer(
-                payer_account.key,
+                source_account.key,
                 registered_authorized_withdrawer.key,
                 to_owner,
             ),
             &[
-                payer_account.clone(),
+                source_account.clone(),
                 registered_authorized_withdrawer.clone(),
             ],
+            source_seeds,
         )
         .error_log("Error @ transfer to owner")?;
 
         if to_escrow > 0 {
-            invoke(
-                &system_instruction::transfer(payer_account.key, escrow_account.key, to_escrow),
-                &[payer_account.clone(), escrow_account.clone()],
+            invoke_signed(
+                &system_instruction::transfer(source_account.key, escrow_account.key, to_escrow),
+                &[source_account.clone(), escrow_account.clone()],
+                source_seeds,
             )
             .error_log("Error @ transfer to escrow")?;
         }
 
         if to_team > 0 {
-            invoke(
-                &system_instruction::transfer(payer_account.key, team_account.key, to_team),
-                &[payer_account.clone(), team_account.clone()],
+            invoke_signed(
+                &system_instruction::transfer(source_account.key, team_account.key, to_team),
+                &[source_account.clone(), team_account.clone()],
+                source_seeds,
             )
             .error_log("Error @ transfer to team")?;
         }
         Ok(())
     };
 
-    do_transfers().error_log("Error @ do_transfer")?;
+    do_transfers().error_log("Error @ do_transfer")
+}
+
+/// Pays out a sale at `price` in the listing's payment mint. The buyer covers any Token-2022
+/// transfer fee, so the seller, the escrow and the team each receive their full share.
+pub fn transfer_sale_proceeds_in_tokens<'a>(
+    payer_account: &AccountInfo<'a>,
+    token_payment_accounts: &[AccountInfo<'a>],
+    storage_data: &Storage,
+    payment_mint: &Pubkey,
+    price: u64,
+    escrow_account: &AccountInfo<'a>,
+) -> ProgramResult {
+    let account_info_iter = &mut token_payment_accounts.iter();
+    let token_program_info = next_account_info(account_info_iter)?;
+    let payment_mint_info = next_account_info(account_info_iter)?;
+    let payer_token_account_info = next_account_info(account_info_iter)?;
+    let seller_token_account_info = next_account_info(account_info_iter)?;
+    let escrow_token_account_info = next_account_info(account_info_iter)?;
+    let team_token_account_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let associated_token_program_info = next_account_info(account_info_iter)?;
+
+    assert_token_program_and_mint(token_program_info, payment_mint_info, payment_mint)?;
+    assert_token_account(
+        payer_token_account_info,
+        token_program_info.key,
+        payment_mint,
+        payer_account.key,
+    )
+    .error_log("Error @ payer token account assertion")?;
+    assert_token_account(
+        seller_token_account_info,
+        token_program_info.key,
+        payment_mint,
+        &storage_data.authorized_withdrawer,
+    )
+    .error_log("Error @ seller token account assertion")?;
+    assert_escrow_token_account(
+        escrow_token_account_info,
+        escrow_account,
+        payment_mint,
+        token_program_info.key,
+    )?;
+    create_escrow_token_account(
+        payer_account,
+        escrow_token_account_info,
+        escrow_account,
+        payment_mint_info,
+        system_program_info,
+        token_program_info,
+        associated_token_program_info,
+    )?;
+    assert_token_account(
+        team_token_account_info,
+        token_program_info.key,
+        payment_mint,
+        &TEAM_ADDRESS,
+    )
+    .error_log("Error @ team token account assertion")?;
+
+    let (to_owner, to_escrow, to_team) = sale_proceeds_split(storage_data, price)?;
+
+    for (destination, amount) in [
+        (seller_token_account_info, to_owner),
+        (escrow_token_account_info, to_escrow),
+        (team_token_account_info, to_team),
+    ] {
+        if amount > 0 {
+            transfer_tokens(
+                token_program_info,
+                payer_token_account_info,
+                payment_mint_info,
+                destination,
+                payer_account,
+                amount
+                    .checked_add(get_inverse_transfer_fee(payment_mint_info, amount)?)
+                    .error_log("amount + transfer fee overflows")?,
+                &[],
+            )?;
+        }
+    }
 
+    Ok(())
+}
+
+pub fn record_purchase(
+    storage_data: &mut Storage,
+    buyer: &Pubkey,
+    authorized_voter: &Pubkey,
+    escrowed: u64,
+    clock_data: &Clock,
+) {
     storage_data.purchase = Some(Purchase {
-        buyer: *payer_account.key,
+        buyer: *buyer,
+        authorized_voter: *authorized_voter,
         date: clock_data.unix_timestamp as u32,
         date_finalized: if storage_data.secondary_items.is_empty() {
             Some(clock_data.unix_timestamp as u32)
         } else {
             None
         },
+        escrowed,
+        escrow_nonce: storage_data.date_listed,
     });
-
-    storage_data
-        .serialize(&mut &mut storage_account.data.borrow_mut()[..])
-        .error_log("Error @ storage serialize")?;
-    Ok(())
 }
 
 pub fn change_authorized_withdrawer<'a>(
@@ -218,3 +566,105 @@ pub fn change_authorized_withdrawer<'a>(
 
     Ok(())
 }
+
+/// Logs when the authorized voter of `vote_account` is no longer the one recorded when it was
+/// listed. The sale goes through regardless, since the program overrides the voter as the
+/// authorized withdrawer, so a seller rotating it can't hold the handover back.
+pub fn log_authorized_voter_change(
+    storage_data: &Storage,
+    vote_account: &AccountInfo,
+    clock_data: &Clock,
+    log_level: LogLevel,
+) {
+    let authorized_voter = AuthorizedVoters::from_vote_account_data(&vote_account.data.borrow())
+        .and_then(|authorized_voters| authorized_voters.get_authorized_voter(clock_data.epoch));
+    if authorized_voter != storage_data.listed_authorized_voter(vote_account.key) {
+        log!(
+            log_level,
+            3,
+            "authorized voter changed since listing: {:?}",
+            authorized_voter
+        );
+    }
+}
+
+/// Assigns a new authorized voter, signed by the program as the authorized withdrawer. The vote
+/// program lets the withdrawer replace the voter, which takes effect from the next leader
+/// schedule epoch.
+pub fn change_authorized_voter<'a>(
+    program_id: &Pubkey,
+    vote_account: &AccountInfo<'a>,
+    new_authorized_voter: &Pubkey,
+    pda_authorized_withdrawer: &AccountInfo<'a>,
+    sysvar_clock_account: &AccountInfo<'a>,
+) -> ProgramResult {
+    vote_account
+        .assert_owner(&vote::program::ID)
+        .error_log("vote_account must be owned by vote_program")?;
+
+    let (_pda_authorized_withdrawer_key, pda_aw_bump) = pda_authorized_withdrawer
+        .assert_seed(program_id, &[PDA_AUTHORIZED_WITHDRAWER_SEED])
+        .error_log("Error @ pda_authorized_withdrawer_info.assert_seed")?;
+
+    invoke_signed(
+        &authorize(
+            vote_account.key,
+            pda_authorized_withdrawer.key,
+            new_authorized_voter,
+            VoteAuthorize::Voter,
+        ),
+        &[
+            vote_account.clone(),
+            sysvar_clock_account.clone(),
+            pda_authorized_withdrawer.clone(),
+        ],
+        &[&[PDA_AUTHORIZED_WITHDRAWER_SEED, &[pda_aw_bump]]],
+    )
+    .error_log("Error switching authorized voter")?;
+
+    Ok(())
+}
+
+/// Hands the staker and withdrawer of the self-stake accounts, held by the program since the
+/// listing, over to `new_authority`.
+pub fn change_stake_authorities<'a>(
+    program_id: &Pubkey,
+    self_stake: &[SelfStake],
+    stake_account_infos: &[AccountInfo<'a>],
+    new_authority: &Pubkey,
+    pda_authorized_withdrawer: &AccountInfo<'a>,
+    sysvar_clock_account: &AccountInfo<'a>,
+) -> ProgramResult {
+    if stake_account_infos.len() < self_stake.len() {
+        Err(InglError::InvalidData.utilize("Error @ self-stake accounts are missing"))?
+    }
+    let (_pda_authorized_withdrawer_key, pda_aw_bump) = pda_authorized_withdrawer
+        .assert_seed(program_id, &[PDA_AUTHORIZED_WITHDRAWER_SEED])
+        .error_log("Error @ pda_authorized_withdrawer_info.assert_seed")?;
+
+    for (stake_account_info, stake) in stake_account_infos.iter().zip(self_stake) {
+        stake_account_info
+            .assert_key_match(&stake.stake_account)
+            .error_log("Error @ stake_account_info.assert_key_match")?;
+        for stake_authorize in [StakeAuthorize::Staker, StakeAuthorize::Withdrawer] {
+            invoke_signed(
+                &stake::instruction::authorize(
+                    stake_account_info.key,
+                    pda_authorized_withdrawer.key,
+                    new_authority,
+                    stake_authorize,
+                    None,
+                ),
+                &[
+                    stake_account_info.clone(),
+                    sysvar_clock_account.clone(),
+                    pda_authorized_withdrawer.clone(),
+                ],
+                &[&[PDA_AUTHORIZED_WITHDRAWER_SEED, &[pda_aw_bump]]],
+            )
+            .error_log("Error switching stake authority")?;
+        }
+    }
+
+    Ok(())
+}
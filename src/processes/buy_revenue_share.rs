@@ -0,0 +1,142 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+
+use crate::{
+    error::InglError,
+    log,
+    processes::buy::sale_proceeds_split,
+    state::{
+        consts::{MAX_REVENUE_SHARE_HOLDERS, PROGRAM_STORAGE_SEED, TEAM_ADDRESS},
+        LogLevel, RevenueShareHolder, Storage,
+    },
+    utils::{get_clock_data_from_account, AccountInfoHelpers, OptionExt, ResultExt},
+};
+
+/// Buys `basis_points` of the future vote rewards of a revenue share listing, priced pro rata
+/// to the listing cost. The seller keeps the vote account and the remaining rewards.
+pub fn buy_revenue_share(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    basis_points: u16,
+    log_level: LogLevel,
+) -> ProgramResult {
+    log!(log_level, 4, "buy_revenue_share called");
+    let account_info_iter = &mut accounts.iter();
+    let buyer_account_info = next_account_info(account_info_iter)?;
+    let storage_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let registered_authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    let team_account_info = next_account_info(account_info_iter)?;
+    let sysvar_clock_account_info = next_account_info(account_info_iter)?;
+    let _system_program_info = next_account_info(account_info_iter)?;
+
+    let clock_data =
+        get_clock_data_from_account(sysvar_clock_account_info).error_log("Error @ clock parse")?;
+
+    buyer_account_info
+        .assert_signer()
+        .error_log("Error @ buyer_account_info.assert_signer")?;
+    storage_account_info
+        .assert_seed(
+            program_id,
+            &[PROGRAM_STORAGE_SEED, vote_account_info.key.as_ref()],
+        )
+        .error_log("Error @ storage_account_info.assert_seed")?;
+    team_account_info
+        .assert_key_match(&TEAM_ADDRESS)
+        .error_log("Error @ team_account_info.assert_key_match")?;
+
+    let mut storage_data = Storage::parse(storage_account_info, program_id)?;
+    registered_authorized_withdrawer_info
+        .assert_key_match(&storage_data.authorized_withdrawer)
+        .error_log("Error @ registered_authorized_withdrawer_info.assert_key_match")?;
+
+    if storage_data.is_expired(clock_data.unix_timestamp as u32) {
+        Err(InglError::TooLate.utilize("listing has expired"))?
+    }
+    storage_data
+        .assert_allowed_buyer(buyer_account_info.key)
+        .error_log("Error @ storage_data.assert_allowed_buyer")?;
+
+    let cost = storage_data.authorized_withdrawer_cost;
+    let revenue_share = storage_data
+        .revenue_share
+        .as_mut()
+        .error_log("Error @ listing does not sell revenue shares")?;
+    if basis_points == 0
+        || basis_points > revenue_share.basis_points - revenue_share.sold_basis_points()
+    {
+        Err(InglError::BeyondBounds.utilize("not enough revenue share left for sale"))?
+    }
+
+    let price = (cost as u128)
+        .checked_mul(basis_points.into())
+        .error_log("price mul calculation error")?
+        .checked_div(revenue_share.basis_points.into())
+        .error_log("price div calculation error")? as u64;
+
+    match revenue_share
+        .holders
+        .iter_mut()
+        .find(|holder| holder.holder == *buyer_account_info.key)
+    {
+        Some(holder) => holder.basis_points += basis_points,
+        None => {
+            if revenue_share.holders.len() >= MAX_REVENUE_SHARE_HOLDERS {
+                Err(InglError::BeyondBounds.utilize("too many revenue share holders"))?
+            }
+            revenue_share.holders.push(RevenueShareHolder {
+                holder: *buyer_account_info.key,
+                basis_points,
+            });
+        }
+    }
+
+    let (to_owner, _to_escrow, to_team) = sale_proceeds_split(&storage_data, price)?;
+    log!(log_level, 2, "buy_revenue_share: transferring payment");
+    for (destination, lamports) in [
+        (registered_authorized_withdrawer_info, to_owner),
+        (team_account_info, to_team),
+    ] {
+        if lamports > 0 {
+            invoke(
+                &system_instruction::transfer(buyer_account_info.key, destination.key, lamports),
+                &[buyer_account_info.clone(), destination.clone()],
+            )
+            .error_log("Error @ revenue share payment")?;
+        }
+    }
+
+    let space = storage_data.get_space();
+    let required_lamports = Rent::get()?.minimum_balance(space);
+    let current_lamports = storage_account_info.lamports();
+    if required_lamports > current_lamports {
+        log!(log_level, 2, "buy_revenue_share: topping up storage rent");
+        invoke(
+            &system_instruction::transfer(
+                buyer_account_info.key,
+                storage_account_info.key,
+                required_lamports - current_lamports,
+            ),
+            &[buyer_account_info.clone(), storage_account_info.clone()],
+        )
+        .error_log("Error @ storage rent top up")?;
+    }
+
+    storage_account_info
+        .realloc(space, false)
+        .error_log("Error @ storage_account_info.realloc")?;
+    storage_data
+        .serialize(&mut &mut storage_account_info.data.borrow_mut()[..])
+        .error_log("Error @ storage_data.serialize")?;
+
+    Ok(())
+}
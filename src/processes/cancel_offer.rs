@@ -0,0 +1,54 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    log,
+    state::{consts::OFFER_SEED, LogLevel, Offer},
+    utils::{move_lamports, AccountInfoHelpers, ResultExt},
+};
+
+/// Closes an offer and returns everything it holds to the buyer. Expired offers and offers
+/// on listings that have since been sold or delisted are reclaimed the same way.
+pub fn cancel_offer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: LogLevel,
+) -> ProgramResult {
+    log!(log_level, 4, "cancel_offer called");
+    let account_info_iter = &mut accounts.iter();
+    let buyer_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let offer_account_info = next_account_info(account_info_iter)?;
+
+    buyer_account_info
+        .assert_signer()
+        .error_log("Error @ buyer_account_info.assert_signer")?;
+    offer_account_info
+        .assert_seed(
+            program_id,
+            &[
+                OFFER_SEED,
+                vote_account_info.key.as_ref(),
+                buyer_account_info.key.as_ref(),
+            ],
+        )
+        .error_log("Error @ offer_account_info.assert_seed")?;
+
+    let offer_data = Offer::parse(offer_account_info, program_id)?;
+    buyer_account_info
+        .assert_key_match(&offer_data.buyer)
+        .error_log("Error @ buyer_account_info.assert_key_match")?;
+
+    close_offer(offer_account_info, buyer_account_info)
+}
+
+/// Zeroes the offer account and sends its remaining lamports to `destination`.
+pub fn close_offer(offer_account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+    move_lamports(offer_account, destination, offer_account.lamports())
+        .error_log("Error @ closing offer account")?;
+    offer_account.data.borrow_mut().fill(0);
+    Ok(())
+}
@@ -0,0 +1,226 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+};
+
+use crate::{
+    error::InglError,
+    log,
+    state::{
+        consts::{CLAIMS_SEED, CLAIMS_VALIDATION_PHRASE, ESCROW_ACCOUNT_SEED},
+        Claim, Claims, LogLevel, Purchase,
+    },
+    utils::{
+        assert_escrow_token_account, assert_token_account, assert_token_program_and_mint,
+        create_pda_account, move_lamports, transfer_tokens, AccountInfoHelpers, OptionExt,
+        ResultExt,
+    },
+};
+
+/// Withdraws what the signer is owed out of the escrow of a finalized purchase. The claims
+/// account is closed to its rent payer once every claim is withdrawn.
+pub fn claim(program_id: &Pubkey, accounts: &[AccountInfo], log_level: LogLevel) -> ProgramResult {
+    log!(log_level, 4, "claim called");
+    let account_info_iter = &mut accounts.iter();
+    let claimant_account_info = next_account_info(account_info_iter)?;
+    let claims_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let escrow_account_info = next_account_info(account_info_iter)?;
+    let rent_payer_account_info = next_account_info(account_info_iter)?;
+    // Token priced listings also take the token program, the payment mint and the escrow and
+    // claimant token accounts, in that order.
+
+    claimant_account_info
+        .assert_signer()
+        .error_log("Error @ claimant_account_info.assert_signer")?;
+
+    let mut claims_data = Claims::parse(claims_account_info, program_id)?;
+    let escrow_seed = Purchase::escrow_seed(claims_data.escrow_nonce);
+    claims_account_info
+        .assert_seed(
+            program_id,
+            &[
+                CLAIMS_SEED,
+                vote_account_info.key.as_ref(),
+                &escrow_seed,
+                &Purchase::item_seed(claims_data.item_index),
+            ],
+        )
+        .error_log("Error @ claims_account_info.assert_seed")?;
+    let (_escrow_key, escrow_bump) = escrow_account_info
+        .assert_seed(
+            program_id,
+            &[
+                ESCROW_ACCOUNT_SEED,
+                vote_account_info.key.as_ref(),
+                &escrow_seed,
+            ],
+        )
+        .error_log("Error @ escrow_account_info.assert_seed")?;
+    rent_payer_account_info
+        .assert_key_match(&claims_data.rent_payer)
+        .error_log("Error @ rent_payer_account_info.assert_key_match")?;
+
+    let claim = claims_data
+        .claims
+        .iter_mut()
+        .find(|claim| claim.claimant == *claimant_account_info.key && claim.amount > 0)
+        .error_log("Error @ nothing to claim")?;
+    let amount = claim.amount;
+    claim.amount = 0;
+
+    log!(log_level, 2, "claim: withdrawing {}", amount);
+    match claims_data.payment_mint {
+        None => move_lamports(claims_account_info, claimant_account_info, amount)
+            .error_log("Error @ claim transfer")?,
+        Some(payment_mint) => {
+            let token_program_info = next_account_info(account_info_iter)?;
+            let payment_mint_info = next_account_info(account_info_iter)?;
+            let escrow_token_account_info = next_account_info(account_info_iter)?;
+            let claimant_token_account_info = next_account_info(account_info_iter)?;
+
+            assert_token_program_and_mint(token_program_info, payment_mint_info, &payment_mint)?;
+            assert_escrow_token_account(
+                escrow_token_account_info,
+                escrow_account_info,
+                &payment_mint,
+                token_program_info.key,
+            )?;
+            assert_token_account(
+                claimant_token_account_info,
+                token_program_info.key,
+                &payment_mint,
+                claimant_account_info.key,
+            )
+            .error_log("Error @ claimant token account assertion")?;
+
+            transfer_tokens(
+                token_program_info,
+                escrow_token_account_info,
+                payment_mint_info,
+                claimant_token_account_info,
+                escrow_account_info,
+                amount,
+                &[&[
+                    ESCROW_ACCOUNT_SEED,
+                    vote_account_info.key.as_ref(),
+                    &escrow_seed,
+                    &[escrow_bump],
+                ]],
+            )
+            .error_log("Error @ claim token transfer")?;
+        }
+    }
+
+    if claims_data.claims.iter().all(|claim| claim.amount == 0) {
+        log!(log_level, 2, "claim: closing claims account");
+        move_lamports(
+            claims_account_info,
+            rent_payer_account_info,
+            claims_account_info.lamports(),
+        )
+        .error_log("Error @ closing claims account")?;
+        claims_account_info.data.borrow_mut().fill(0);
+    } else {
+        claims_data
+            .serialize(&mut &mut claims_account_info.data.borrow_mut()[..])
+            .error_log("Error @ claims_data.serialize")?;
+    }
+
+    Ok(())
+}
+
+/// Records what each of `claims` is owed out of the escrow of `purchase`, for the secondary item
+/// at `item_index` or for the rest of the escrow, in a new claims account whose rent `payer`
+/// covers. Escrowed lamports move into the claims account, while escrowed tokens stay in the
+/// escrow token account until claimed. Nothing is recorded when every claim is zero.
+pub fn record_claims<'a>(
+    program_id: &Pubkey,
+    purchase: &Purchase,
+    item_index: Option<u32>,
+    payment_mint: Option<Pubkey>,
+    vote_account: &AccountInfo<'a>,
+    escrow_account: &AccountInfo<'a>,
+    claims_account: &AccountInfo<'a>,
+    payer_account: &AccountInfo<'a>,
+    claims: &[Claim],
+) -> ProgramResult {
+    let escrow_seed = Purchase::escrow_seed(purchase.escrow_nonce);
+    let item_seed = Purchase::item_seed(item_index);
+    let (_escrow_key, escrow_bump) = escrow_account
+        .assert_seed(
+            program_id,
+            &[ESCROW_ACCOUNT_SEED, vote_account.key.as_ref(), &escrow_seed],
+        )
+        .error_log("Error @ escrow_account.assert_seed")?;
+    let (_claims_key, claims_bump) = claims_account
+        .assert_seed(
+            program_id,
+            &[
+                CLAIMS_SEED,
+                vote_account.key.as_ref(),
+                &escrow_seed,
+                &item_seed,
+            ],
+        )
+        .error_log("Error @ claims_account.assert_seed")?;
+
+    let claimed: u64 = claims.iter().map(|claim| claim.amount).sum();
+    if claimed > purchase.escrowed {
+        Err(InglError::BeyondBounds.utilize("claims exceed the escrowed amount"))?
+    }
+    if claimed == 0 {
+        return Ok(());
+    }
+
+    let claims_data = Claims {
+        validation_phrase: CLAIMS_VALIDATION_PHRASE,
+        vote_account: *vote_account.key,
+        escrow_nonce: purchase.escrow_nonce,
+        item_index,
+        payment_mint,
+        rent_payer: *payer_account.key,
+        claims: claims
+            .iter()
+            .filter(|claim| claim.amount > 0)
+            .copied()
+            .collect(),
+    };
+    create_pda_account(
+        program_id,
+        payer_account,
+        claims_account,
+        claims_data.get_space(),
+        &[
+            CLAIMS_SEED,
+            vote_account.key.as_ref(),
+            &escrow_seed,
+            &item_seed,
+            &[claims_bump],
+        ],
+    )
+    .error_log("Error @ create_pda_account")?;
+
+    if payment_mint.is_none() {
+        invoke_signed(
+            &system_instruction::transfer(escrow_account.key, claims_account.key, claimed),
+            &[escrow_account.clone(), claims_account.clone()],
+            &[&[
+                ESCROW_ACCOUNT_SEED,
+                vote_account.key.as_ref(),
+                &escrow_seed,
+                &[escrow_bump],
+            ]],
+        )
+        .error_log("Error @ escrow transfer to claims account")?;
+    }
+
+    claims_data
+        .serialize(&mut &mut claims_account.data.borrow_mut()[..])
+        .error_log("Error @ claims_data.serialize")?;
+    Ok(())
+}
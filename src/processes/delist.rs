@@ -1,26 +1,28 @@
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
-    bpf_loader_upgradeable,
+    clock::Clock,
     entrypoint::ProgramResult,
     program::invoke_signed,
     program_error::ProgramError,
     pubkey::Pubkey,
-    sysvar,
+    sysvar::{self, Sysvar},
     vote::{self, instruction::authorize, state::VoteAuthorize},
 };
 
 use crate::{
     error::InglError,
     log,
+    processes::buy::change_stake_authorities,
     state::{
-        consts::{
-            PDA_AUTHORIZED_WITHDRAWER_SEED, PDA_UPGRADE_AUTHORITY_SEED, PROGRAM_STORAGE_SEED,
-        },
-        LogLevel, Storage,
+        consts::{BUYBACK_SEED, PDA_AUTHORIZED_WITHDRAWER_SEED, PROGRAM_STORAGE_SEED},
+        Buyback, BuybackClause, LogLevel, Storage, StoredListingType,
     },
-    utils::{AccountInfoHelpers, OptionExt, ResultExt},
+    utils::{get_clock_data_from_account, AccountInfoHelpers, OptionExt, ResultExt},
 };
 
+/// Closes a listing, returning the storage rent to the seller. The seller signs to take back an
+/// unsold listing, while anyone can close a sold one once its purchase is finalized, so that the
+/// buyer can relist the validator.
 pub fn delist_validator(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -32,19 +34,34 @@ pub fn delist_validator(
     let vote_account_info = next_account_info(account_info_iter)?;
     let pda_authorized_withdrawer_info = next_account_info(account_info_iter)?;
     let storage_account_info = next_account_info(account_info_iter)?;
-    let this_program_account_info = next_account_info(account_info_iter)?;
-    let this_program_data_account_info = next_account_info(account_info_iter)?;
-    let pda_upgrade_authority_info = next_account_info(account_info_iter)?;
     let sysvar_clock_account_info = next_account_info(account_info_iter)?;
+    // Bundles also take their other vote accounts, in the order they are recorded, followed by
+    // the self-stake accounts of the listing. Sold listings with a buyback call option take the
+    // buyback account instead.
+    let trailing_accounts = account_info_iter.as_slice();
 
-    log!(log_level, 2, "delist_validator: change_program_authority");
-    change_program_authority(
-        program_id,
-        authorized_withdrawer_info,
-        pda_upgrade_authority_info,
-        this_program_account_info,
-        this_program_data_account_info,
-    )?;
+    let Storage {
+        bundled_vote_accounts,
+        self_stake,
+        purchase,
+        buyback_clause,
+        ..
+    } = Storage::parse(storage_account_info, program_id)?;
+    if purchase.is_none() {
+        authorized_withdrawer_info
+            .assert_signer()
+            .error_log("Error @ authorized_withdrawer_info.assert_signer")?;
+    } else if let Some(BuybackClause::CallOption { .. }) = buyback_clause {
+        let buyback_account_info = trailing_accounts
+            .first()
+            .error_log("Error @ buyback account is missing")?;
+        assert_no_active_call_option(
+            program_id,
+            buyback_account_info,
+            vote_account_info,
+            &get_clock_data_from_account(sysvar_clock_account_info)?,
+        )?;
+    }
 
     log!(log_level, 2, "delist_validator: closing storage");
     let is_sold = verify_and_close_storage(
@@ -66,6 +83,78 @@ pub fn delist_validator(
             pda_authorized_withdrawer_info,
             sysvar_clock_account_info,
         )?;
+        return_bundled_authorized_withdrawers(
+            program_id,
+            &bundled_vote_accounts,
+            trailing_accounts,
+            authorized_withdrawer_info,
+            pda_authorized_withdrawer_info,
+            sysvar_clock_account_info,
+        )?;
+        change_stake_authorities(
+            program_id,
+            &self_stake,
+            trailing_accounts
+                .get(bundled_vote_accounts.len()..)
+                .unwrap_or_default(),
+            authorized_withdrawer_info.key,
+            pda_authorized_withdrawer_info,
+            sysvar_clock_account_info,
+        )?;
+    }
+    Ok(())
+}
+
+/// Returns the withdrawers of the other vote accounts of an unsold bundle to the seller.
+pub fn return_bundled_authorized_withdrawers<'a>(
+    program_id: &Pubkey,
+    bundled_vote_accounts: &[Pubkey],
+    bundled_vote_account_infos: &[AccountInfo<'a>],
+    authorized_withdrawer: &AccountInfo<'a>,
+    pda_authorized_withdrawer: &AccountInfo<'a>,
+    sysvar_clock_account: &AccountInfo<'a>,
+) -> ProgramResult {
+    if bundled_vote_account_infos.len() < bundled_vote_accounts.len() {
+        Err(InglError::InvalidData.utilize("Error @ bundled vote accounts are missing"))?
+    }
+    for (bundled_vote_account_info, bundled_vote_account) in
+        bundled_vote_account_infos.iter().zip(bundled_vote_accounts)
+    {
+        bundled_vote_account_info
+            .assert_key_match(bundled_vote_account)
+            .error_log("Error @ bundled_vote_account_info.assert_key_match")?;
+        change_authorized_withdrawer(
+            program_id,
+            bundled_vote_account_info,
+            authorized_withdrawer,
+            pda_authorized_withdrawer,
+            sysvar_clock_account,
+        )?;
+    }
+    Ok(())
+}
+
+/// The buyer's rewards are withdrawn through the storage, so it has to outlive the window in which
+/// the seller can still exercise a call option.
+pub fn assert_no_active_call_option(
+    program_id: &Pubkey,
+    buyback_account: &AccountInfo,
+    vote_account: &AccountInfo,
+    clock_data: &Clock,
+) -> ProgramResult {
+    buyback_account
+        .assert_seed(program_id, &[BUYBACK_SEED, vote_account.key.as_ref()])
+        .error_log("Error @ buyback_account.assert_seed")?;
+    // An exercised or expired call option has its account closed.
+    if buyback_account.lamports() == 0 || buyback_account.owner != program_id {
+        return Ok(());
+    }
+    let buyback_data = Buyback::parse(buyback_account, program_id)?;
+    if matches!(buyback_data.clause, BuybackClause::CallOption { .. })
+        && buyback_data.is_active(clock_data.unix_timestamp as u32)
+    {
+        Err(InglError::TooEarly
+            .utilize("A listing can't be delisted while its buyback call option is active"))?
     }
     Ok(())
 }
@@ -77,12 +166,11 @@ pub fn verify_and_close_storage<'a>(
     vote_account: &AccountInfo<'a>,
 ) -> Result<bool, ProgramError> {
     storage_account
-        .assert_seed(program_id, &[PROGRAM_STORAGE_SEED])
+        .assert_seed(
+            program_id,
+            &[PROGRAM_STORAGE_SEED, vote_account.key.as_ref()],
+        )
         .error_log("Error @ storage_account_info.assert_seed")?;
-    payer_account
-        .assert_signer()
-        .error_log("Error @ payer_account_info.assert_signer")?;
-
     let storage_data =
         Storage::parse(storage_account, program_id).error_log("Error @ Storage::parse")?;
 
@@ -93,16 +181,40 @@ pub fn verify_and_close_storage<'a>(
     vote_account
         .assert_key_match(&storage_data.vote_account)
         .error_log("Error @ vote_account_info.assert_key_match")?;
+    if let StoredListingType::EnglishAuction(auction) = &storage_data.listing_type {
+        if auction.highest_bid.is_some() && storage_data.purchase.is_none() {
+            Err(InglError::TooEarly
+                .utilize("An auction with a standing bid must be settled before delisting"))?
+        }
+    }
+    if let StoredListingType::Lease(lease) = &storage_data.listing_type {
+        if lease.lessee.is_some() && !lease.has_ended(Clock::get()?.epoch) {
+            Err(InglError::TooEarly.utilize("A lease can't be delisted before it ends"))?
+        }
+    }
+
+    if let Some(revenue_share) = &storage_data.revenue_share {
+        if !revenue_share.holders.is_empty() {
+            Err(InglError::TooLate
+                .utilize("A listing with revenue share holders can't be delisted"))?
+        }
+    }
+
     let mut is_sold = false;
-    if let Some(_purchase) = storage_data.purchase {
+    if let Some(purchase) = storage_data.purchase {
         is_sold = true;
-        for item in &storage_data.secondary_items {
-            match item.date_validated {
-                None => Err(InglError::TooEarly
-                    .utilize("One must wait for all secondary item transfers to be finalized"))?,
-                Some(_) => (),
+        if let Some(installment_plan) = &storage_data.installment_plan {
+            if !installment_plan.is_paid_in_full() {
+                Err(InglError::TooEarly
+                    .utilize("A listing with outstanding installments can't be delisted"))?
             }
         }
+        // A purchase finalized without every item delivered, through mediation or a refund,
+        // can be closed too, unless the identity rotation still needs the program.
+        if purchase.date_finalized.is_none() || storage_data.has_pending_identity_rotation() {
+            Err(InglError::TooEarly
+                .utilize("One must wait for all secondary item transfers to be finalized"))?
+        }
     }
 
     let storage_account_lamports = storage_account.lamports();
@@ -153,43 +265,3 @@ pub fn change_authorized_withdrawer<'a>(
 
     Ok(())
 }
-
-pub fn change_program_authority<'a>(
-    program_id: &Pubkey,
-    authorized_withdrawer: &AccountInfo<'a>,
-    pda_authority: &AccountInfo<'a>,
-    this_program: &AccountInfo,
-    this_program_data: &AccountInfo<'a>,
-) -> ProgramResult {
-    this_program
-        .assert_owner(&bpf_loader_upgradeable::id())
-        .error_log("Error @ program owner assertion")?;
-    this_program_data
-        .assert_seed(&bpf_loader_upgradeable::id(), &[this_program.key.as_ref()])
-        .error_log("Error @ program data key assertion")?;
-
-    this_program
-        .assert_key_match(program_id)
-        .error_log("Error @ program key assertion")?;
-
-    let (_pda_authority_key, pda_authority_bump) = pda_authority
-        .assert_seed(program_id, &[PDA_UPGRADE_AUTHORITY_SEED])
-        .error_log("Error @ pda_upgrade_authority.assert_seed")?;
-
-    invoke_signed(
-        &bpf_loader_upgradeable::set_upgrade_authority(
-            this_program.key,
-            pda_authority.key,
-            Some(authorized_withdrawer.key),
-        ),
-        &[
-            this_program_data.clone(),
-            pda_authority.clone(),
-            authorized_withdrawer.clone(),
-        ],
-        &[&[PDA_UPGRADE_AUTHORITY_SEED, &[pda_authority_bump]]],
-    )
-    .error_log("Error setting upgrade authority")?;
-
-    Ok(())
-}
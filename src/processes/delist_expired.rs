@@ -0,0 +1,93 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    error::InglError,
+    log,
+    processes::{
+        buy::change_stake_authorities,
+        delist::{
+            change_authorized_withdrawer, return_bundled_authorized_withdrawers,
+            verify_and_close_storage,
+        },
+    },
+    state::{consts::PROGRAM_STORAGE_SEED, LogLevel, Storage},
+    utils::{get_clock_data_from_account, AccountInfoHelpers, ResultExt},
+};
+
+/// Permissionless crank that delists an unsold listing once its expiry date has passed,
+/// returning the authorized withdrawer and the storage rent to the seller.
+pub fn delist_expired(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: LogLevel,
+) -> ProgramResult {
+    log!(log_level, 4, "delist_expired called");
+    let account_info_iter = &mut accounts.iter();
+    let authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let pda_authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    let storage_account_info = next_account_info(account_info_iter)?;
+    let sysvar_clock_account_info = next_account_info(account_info_iter)?;
+    // Bundles also take their other vote accounts, in the order they are recorded, followed by
+    // the self-stake accounts of the listing.
+    let trailing_accounts = account_info_iter.as_slice();
+
+    let clock_data =
+        get_clock_data_from_account(sysvar_clock_account_info).error_log("Error @ clock parse")?;
+
+    storage_account_info
+        .assert_seed(
+            program_id,
+            &[PROGRAM_STORAGE_SEED, vote_account_info.key.as_ref()],
+        )
+        .error_log("Error @ storage_account_info.assert_seed")?;
+    let storage_data = Storage::parse(storage_account_info, program_id)?;
+
+    if let Some(_purchase) = storage_data.purchase {
+        Err(InglError::TooLate.utilize("a sold listing does not expire"))?
+    }
+    if !storage_data.is_expired(clock_data.unix_timestamp as u32) {
+        Err(InglError::TooEarly.utilize("listing has not expired yet"))?
+    }
+
+    log!(log_level, 2, "delist_expired: closing storage");
+    verify_and_close_storage(
+        program_id,
+        storage_account_info,
+        authorized_withdrawer_info,
+        vote_account_info,
+    )?;
+
+    log!(log_level, 2, "delist_expired: change_authorized_withdrawer");
+    change_authorized_withdrawer(
+        program_id,
+        vote_account_info,
+        authorized_withdrawer_info,
+        pda_authorized_withdrawer_info,
+        sysvar_clock_account_info,
+    )?;
+    return_bundled_authorized_withdrawers(
+        program_id,
+        &storage_data.bundled_vote_accounts,
+        trailing_accounts,
+        authorized_withdrawer_info,
+        pda_authorized_withdrawer_info,
+        sysvar_clock_account_info,
+    )?;
+    change_stake_authorities(
+        program_id,
+        &storage_data.self_stake,
+        trailing_accounts
+            .get(storage_data.bundled_vote_accounts.len()..)
+            .unwrap_or_default(),
+        authorized_withdrawer_info.key,
+        pda_authorized_withdrawer_info,
+        sysvar_clock_account_info,
+    )?;
+
+    Ok(())
+}
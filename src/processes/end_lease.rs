@@ -0,0 +1,65 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    error::InglError,
+    log,
+    processes::delist::{change_authorized_withdrawer, verify_and_close_storage},
+    state::{consts::PROGRAM_STORAGE_SEED, LogLevel, Storage, StoredListingType},
+    utils::{get_clock_data_from_account, AccountInfoHelpers, ResultExt},
+};
+
+/// Permissionless crank that ends a lease once its last epoch has passed, returning the
+/// authorized withdrawer and the storage rent to the lessor.
+pub fn end_lease(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: LogLevel,
+) -> ProgramResult {
+    log!(log_level, 4, "end_lease called");
+    let account_info_iter = &mut accounts.iter();
+    let authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let pda_authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    let storage_account_info = next_account_info(account_info_iter)?;
+    let sysvar_clock_account_info = next_account_info(account_info_iter)?;
+
+    let clock_data =
+        get_clock_data_from_account(sysvar_clock_account_info).error_log("Error @ clock parse")?;
+
+    storage_account_info
+        .assert_seed(
+            program_id,
+            &[PROGRAM_STORAGE_SEED, vote_account_info.key.as_ref()],
+        )
+        .error_log("Error @ storage_account_info.assert_seed")?;
+    let storage_data = Storage::parse(storage_account_info, program_id)?;
+
+    match &storage_data.listing_type {
+        StoredListingType::Lease(lease) if lease.has_ended(clock_data.epoch) => (),
+        StoredListingType::Lease(_) => Err(InglError::TooEarly.utilize("lease has not ended yet"))?,
+        _ => Err(InglError::InvalidData.utilize("listing is not a lease"))?,
+    }
+
+    log!(log_level, 2, "end_lease: closing storage");
+    verify_and_close_storage(
+        program_id,
+        storage_account_info,
+        authorized_withdrawer_info,
+        vote_account_info,
+    )?;
+
+    log!(log_level, 2, "end_lease: change_authorized_withdrawer");
+    change_authorized_withdrawer(
+        program_id,
+        vote_account_info,
+        authorized_withdrawer_info,
+        pda_authorized_withdrawer_info,
+        sysvar_clock_account_info,
+    )?;
+
+    Ok(())
+}
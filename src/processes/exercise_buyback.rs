@@ -0,0 +1,99 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke,
+    pubkey::Pubkey,
+    system_instruction,
+};
+
+use crate::{
+    error::InglError,
+    log,
+    processes::{
+        buy::{change_authorized_voter, change_authorized_withdrawer},
+        expire_buyback::close_buyback,
+    },
+    state::{consts::BUYBACK_SEED, Buyback, BuybackClause, LogLevel},
+    utils::{get_clock_data_from_account, AccountInfoHelpers, ResultExt},
+};
+
+/// Exercises a buyback call option. The seller pays the buyer the purchase price plus the agreed
+/// premium and gets the authorized withdrawer back from the program.
+pub fn exercise_buyback(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: LogLevel,
+) -> ProgramResult {
+    log!(log_level, 4, "exercise_buyback called");
+    let account_info_iter = &mut accounts.iter();
+    let seller_account_info = next_account_info(account_info_iter)?;
+    let buyback_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let buyer_account_info = next_account_info(account_info_iter)?;
+    let pda_authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    let sysvar_clock_account_info = next_account_info(account_info_iter)?;
+
+    let clock_data =
+        get_clock_data_from_account(sysvar_clock_account_info).error_log("Error @ clock parse")?;
+
+    seller_account_info
+        .assert_signer()
+        .error_log("Error @ seller_account_info.assert_signer")?;
+    buyback_account_info
+        .assert_seed(program_id, &[BUYBACK_SEED, vote_account_info.key.as_ref()])
+        .error_log("Error @ buyback_account_info.assert_seed")?;
+    let buyback_data = Buyback::parse(buyback_account_info, program_id)?;
+    seller_account_info
+        .assert_key_match(&buyback_data.seller)
+        .error_log("Only the seller can exercise a buyback")?;
+    buyer_account_info
+        .assert_key_match(&buyback_data.buyer)
+        .error_log("Error @ buyer_account_info.assert_key_match")?;
+
+    if !matches!(buyback_data.clause, BuybackClause::CallOption { .. }) {
+        Err(InglError::InvalidData.utilize("buyback clause is not a call option"))?
+    }
+    if !buyback_data.is_active(clock_data.unix_timestamp as u32) {
+        Err(InglError::TooLate.utilize("buyback window has closed"))?
+    }
+
+    log!(log_level, 2, "exercise_buyback: paying the buyer");
+    invoke(
+        &system_instruction::transfer(
+            seller_account_info.key,
+            buyer_account_info.key,
+            buyback_data.exercise_price(),
+        ),
+        &[seller_account_info.clone(), buyer_account_info.clone()],
+    )
+    .error_log("Error @ transfer to buyer")?;
+
+    log!(log_level, 2, "exercise_buyback: change_authorized_voter");
+    change_authorized_voter(
+        program_id,
+        vote_account_info,
+        seller_account_info.key,
+        pda_authorized_withdrawer_info,
+        sysvar_clock_account_info,
+    )
+    .error_log("Error @ change_authorized_voter")?;
+
+    log!(
+        log_level,
+        2,
+        "exercise_buyback: change_authorized_withdrawer"
+    );
+    change_authorized_withdrawer(
+        program_id,
+        vote_account_info,
+        seller_account_info,
+        pda_authorized_withdrawer_info,
+        sysvar_clock_account_info,
+        log_level,
+    )
+    .error_log("Error @ change_authorized_withdrawer")?;
+
+    close_buyback(buyback_account_info, buyer_account_info)?;
+
+    Ok(())
+}
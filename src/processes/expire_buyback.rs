@@ -0,0 +1,69 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    error::InglError,
+    log,
+    processes::buy::change_authorized_withdrawer,
+    state::{consts::BUYBACK_SEED, Buyback, BuybackClause, LogLevel},
+    utils::{get_clock_data_from_account, move_lamports, AccountInfoHelpers, ResultExt},
+};
+
+/// Permissionless crank that closes a buyback clause once it can no longer be exercised.
+/// Under a call option, the authorized withdrawer held by the program is handed to the buyer.
+pub fn expire_buyback(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: LogLevel,
+) -> ProgramResult {
+    log!(log_level, 4, "expire_buyback called");
+    let account_info_iter = &mut accounts.iter();
+    let buyer_account_info = next_account_info(account_info_iter)?;
+    let buyback_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let pda_authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    let sysvar_clock_account_info = next_account_info(account_info_iter)?;
+
+    let clock_data =
+        get_clock_data_from_account(sysvar_clock_account_info).error_log("Error @ clock parse")?;
+
+    buyback_account_info
+        .assert_seed(program_id, &[BUYBACK_SEED, vote_account_info.key.as_ref()])
+        .error_log("Error @ buyback_account_info.assert_seed")?;
+    let buyback_data = Buyback::parse(buyback_account_info, program_id)?;
+    buyer_account_info
+        .assert_key_match(&buyback_data.buyer)
+        .error_log("Error @ buyer_account_info.assert_key_match")?;
+
+    if buyback_data.is_active(clock_data.unix_timestamp as u32) {
+        Err(InglError::TooEarly.utilize("buyback clause can still be exercised"))?
+    }
+
+    if let BuybackClause::CallOption { .. } = buyback_data.clause {
+        log!(log_level, 2, "expire_buyback: change_authorized_withdrawer");
+        change_authorized_withdrawer(
+            program_id,
+            vote_account_info,
+            buyer_account_info,
+            pda_authorized_withdrawer_info,
+            sysvar_clock_account_info,
+            log_level,
+        )
+        .error_log("Error @ change_authorized_withdrawer")?;
+    }
+
+    close_buyback(buyback_account_info, buyer_account_info)?;
+
+    Ok(())
+}
+
+/// Returns the rent of the buyback account to the buyer who paid for it.
+pub fn close_buyback(buyback_account: &AccountInfo, buyer_account: &AccountInfo) -> ProgramResult {
+    move_lamports(buyback_account, buyer_account, buyback_account.lamports())
+        .error_log("Error @ closing buyback account")?;
+    buyback_account.data.borrow_mut().fill(0);
+    Ok(())
+}
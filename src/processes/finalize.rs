@@ -0,0 +1,98 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    error::InglError,
+    log,
+    processes::claim::record_claims,
+    state::{consts::PROGRAM_STORAGE_SEED, Claim, LogLevel, Storage},
+    utils::{get_clock_data_from_account, AccountInfoHelpers, OptionExt, ResultExt},
+};
+
+/// Lets the seller finalize a purchase once the buyer stayed silent for the silence interval,
+/// neither validating the secondary items nor requesting mediation. The whole escrow, including
+/// the buyer's secondary items deposit, becomes claimable by the seller.
+pub fn finalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: LogLevel,
+) -> ProgramResult {
+    log!(log_level, 4, "finalize called");
+    let account_info_iter = &mut accounts.iter();
+    let authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    let storage_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let escrow_account_info = next_account_info(account_info_iter)?;
+    let claims_account_info = next_account_info(account_info_iter)?;
+    let sysvar_clock_account_info = next_account_info(account_info_iter)?;
+
+    let clock_data =
+        get_clock_data_from_account(sysvar_clock_account_info).error_log("Error @ clock parse")?;
+
+    authorized_withdrawer_info
+        .assert_signer()
+        .error_log("Error @ authorized_withdrawer_info.assert_signer")?;
+    storage_account_info
+        .assert_seed(
+            program_id,
+            &[PROGRAM_STORAGE_SEED, vote_account_info.key.as_ref()],
+        )
+        .error_log("Error @ storage_account_info.assert_seed")?;
+
+    let mut storage_data = Storage::parse(storage_account_info, program_id)?;
+    authorized_withdrawer_info
+        .assert_key_match(&storage_data.authorized_withdrawer)
+        .error_log("Only the seller can finalize a purchase")?;
+
+    let purchase = storage_data
+        .purchase
+        .error_log("Error @ validator is not bought yet")?;
+    if let Some(_date_finalized) = purchase.date_finalized {
+        Err(InglError::TooLate.utilize("Purchase has already been finalized"))?
+    }
+    if let Some(_request_mediation_date) = storage_data.request_mediation_date {
+        Err(InglError::TooLate.utilize("Mediation has been requested"))?
+    }
+    if storage_data.has_pending_item_dispute() {
+        Err(InglError::TooLate.utilize("Mediation has been requested for a secondary item"))?
+    }
+    if (clock_data.unix_timestamp as u32)
+        <= purchase.date.saturating_add(storage_data.silence_interval)
+    {
+        Err(InglError::TooEarly.utilize("silence interval has not passed yet"))?
+    }
+
+    log!(log_level, 3, "to_seller: {}", purchase.escrowed);
+    record_claims(
+        program_id,
+        &purchase,
+        None,
+        storage_data.payment_mint,
+        vote_account_info,
+        escrow_account_info,
+        claims_account_info,
+        authorized_withdrawer_info,
+        &[Claim {
+            claimant: storage_data.authorized_withdrawer,
+            amount: purchase.escrowed,
+        }],
+    )
+    .error_log("Error @ record_claims")?;
+
+    let purchase = storage_data
+        .purchase
+        .as_mut()
+        .error_log("Error @ validator is not bought yet")?;
+    purchase.date_finalized = Some(clock_data.unix_timestamp as u32);
+    purchase.escrowed = 0;
+
+    storage_data
+        .serialize(&mut &mut storage_account_info.data.borrow_mut()[..])
+        .error_log("Error @ storage_data.serialize")?;
+
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+};
+
+use crate::{
+    error::InglError,
+    log,
+    state::{
+        consts::{ESCROW_ACCOUNT_SEED, PROGRAM_STORAGE_SEED},
+        LogLevel, Purchase, Storage,
+    },
+    utils::{get_clock_data_from_account, AccountInfoHelpers, OptionExt, ResultExt},
+};
+
+/// Forfeits a defaulted installment purchase back to the seller. The buyer gets back the cost and
+/// collateral of the secondary items that were never delivered, the seller keeps the payments made
+/// so far along with the rest of the escrowed funds, and the listing is put back on sale.
+pub fn forfeit_installments(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: LogLevel,
+) -> ProgramResult {
+    log!(log_level, 4, "forfeit_installments called");
+    let account_info_iter = &mut accounts.iter();
+    let authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    let storage_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let escrow_account_info = next_account_info(account_info_iter)?;
+    let buyer_account_info = next_account_info(account_info_iter)?;
+    let sysvar_clock_account_info = next_account_info(account_info_iter)?;
+
+    let clock_data =
+        get_clock_data_from_account(sysvar_clock_account_info).error_log("Error @ clock parse")?;
+
+    authorized_withdrawer_info
+        .assert_signer()
+        .error_log("Error @ authorized_withdrawer_info.assert_signer")?;
+    storage_account_info
+        .assert_seed(
+            program_id,
+            &[PROGRAM_STORAGE_SEED, vote_account_info.key.as_ref()],
+        )
+        .error_log("Error @ storage_account_info.assert_seed")?;
+
+    let mut storage_data = Storage::parse(storage_account_info, program_id)?;
+    authorized_withdrawer_info
+        .assert_key_match(&storage_data.authorized_withdrawer)
+        .error_log("Only the seller can forfeit an installment purchase")?;
+
+    let purchase = storage_data
+        .purchase
+        .error_log("Error @ validator is not bought yet")?;
+    buyer_account_info
+        .assert_key_match(&purchase.buyer)
+        .error_log("Error @ buyer_account_info.assert_key_match")?;
+    let escrow_seed = Purchase::escrow_seed(purchase.escrow_nonce);
+    let (_escrow_key, escrow_bump) = escrow_account_info
+        .assert_seed(
+            program_id,
+            &[
+                ESCROW_ACCOUNT_SEED,
+                vote_account_info.key.as_ref(),
+                &escrow_seed,
+            ],
+        )
+        .error_log("Error @ escrow_account_info.assert_seed")?;
+    if (storage_data.request_mediation_date.is_some() && storage_data.mediation_date.is_none())
+        || storage_data.has_pending_item_dispute()
+    {
+        Err(InglError::TooEarly.utilize("a pending mediation must be resolved first"))?
+    }
+    let installment_plan = storage_data
+        .installment_plan
+        .as_mut()
+        .error_log("Error @ listing has no installment plan")?;
+    if !installment_plan.is_defaulted(purchase.date, clock_data.unix_timestamp as u32) {
+        Err(InglError::TooEarly.utilize("installment plan has not defaulted"))?
+    }
+    installment_plan.installments_paid = 0;
+
+    let to_buyer = storage_data
+        .undelivered_items_refund()
+        .min(purchase.escrowed);
+    log!(
+        log_level,
+        3,
+        "to_buyer: {}, to_seller: {}",
+        to_buyer,
+        purchase.escrowed - to_buyer
+    );
+    for (recipient, amount) in [
+        (buyer_account_info, to_buyer),
+        (authorized_withdrawer_info, purchase.escrowed - to_buyer),
+    ] {
+        if amount > 0 {
+            log!(log_level, 2, "forfeit_installments: releasing escrow");
+            invoke_signed(
+                &system_instruction::transfer(escrow_account_info.key, recipient.key, amount),
+                &[escrow_account_info.clone(), recipient.clone()],
+                &[&[
+                    ESCROW_ACCOUNT_SEED,
+                    vote_account_info.key.as_ref(),
+                    &escrow_seed,
+                    &[escrow_bump],
+                ]],
+            )
+            .error_log("Error @ escrow release")?;
+        }
+    }
+
+    storage_data.purchase = None;
+    // The next purchase gets an escrow, claims and mediation cases of its own.
+    storage_data.date_listed = clock_data.unix_timestamp as u32;
+    storage_data.request_mediation_date = None;
+    storage_data.mediation_date = None;
+    storage_data.mediation_shares = None;
+    for item in storage_data.secondary_items.iter_mut() {
+        item.date_validated = None;
+        item.request_mediation_date = None;
+        item.mediation_shares = None;
+    }
+
+    storage_data
+        .serialize(&mut &mut storage_account_info.data.borrow_mut()[..])
+        .error_log("Error @ storage_data.serialize")?;
+
+    Ok(())
+}
@@ -1,39 +1,64 @@
 use borsh::BorshSerialize;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
-    bpf_loader_upgradeable,
+    clock::Clock,
+    config,
     entrypoint::ProgramResult,
-    program::{invoke, invoke_signed},
+    program::invoke,
+    program_error::ProgramError,
     pubkey::Pubkey,
     rent::Rent,
-    system_instruction, sysvar,
+    stake::{
+        self,
+        state::{StakeAuthorize, StakeState},
+    },
+    stake_history::Epoch,
+    sysvar,
     vote::{self, instruction::authorize, state::VoteAuthorize},
 };
 
 use crate::{
     error::InglError,
-    instruction::{register_program_instruction, SecondaryItem},
+    instruction::{InstallmentTerms, ListingType, SecondaryItem},
     log,
     state::{
         consts::{
-            PDA_AUTHORIZED_WITHDRAWER_SEED, PDA_UPGRADE_AUTHORITY_SEED, PROGRAM_STORAGE_SEED,
-            STORAGE_VALIDATION_PHRASE,
+            BUYBACK_SEED, DEFAULT_SILENCE_INTERVAL, MAX_BUNDLED_VOTE_ACCOUNTS, MAX_MEDIATORS,
+            MAX_SELF_STAKE_ACCOUNTS, MEDIATOR_REGISTRY_SEED, MIN_MEDIATION_PANEL_THRESHOLD,
+            PDA_AUTHORIZED_WITHDRAWER_SEED, PROGRAM_STORAGE_SEED, STORAGE_VALIDATION_PHRASE,
         },
-        LogLevel, Storage,
+        AuthorizedVoters, Buyback, BuybackClause, FirstRefusal, InstallmentPlan, LogLevel,
+        MediationPanel, MediatorRegistry, PriceSchedule, RevenueShare, SecondaryItemKind,
+        SelfStake, Storage, StoredListingType, StoredSecondaryItem, ValidatorInfo,
+    },
+    utils::{
+        create_pda_account_with_rent, get_clock_data_from_account, get_rent_data,
+        AccountInfoHelpers, OptionExt, ResultExt,
     },
-    utils::{get_rent_data, AccountInfoHelpers, ResultExt},
 };
 
 pub fn list_validator(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     authorized_withdrawer_cost: u64,
+    listing_type: ListingType,
     secondary_items: Vec<SecondaryItem>,
     description: String,
     log_level: LogLevel,
     mediation_interval: u32,
     validator_name: String,
     validator_logo_url: String,
+    validator_info: Option<Pubkey>,
+    expires_at: Option<u32>,
+    allowed_buyers: Vec<Pubkey>,
+    payment_mint: Option<Pubkey>,
+    installment_terms: Option<InstallmentTerms>,
+    revenue_share_basis_points: Option<u16>,
+    buyback_clause: Option<BuybackClause>,
+    self_stake_accounts: u8,
+    delivery_interval: Option<u32>,
+    silence_interval: Option<u32>,
+    mediation_panel: Option<MediationPanel>,
     rent_is_from_account: bool,
 ) -> ProgramResult {
     log!(log_level, 4, "list_validator called");
@@ -42,52 +67,284 @@ pub fn list_validator(
     let vote_account_info = next_account_info(account_info_iter)?;
     let pda_authorized_withdrawer_info = next_account_info(account_info_iter)?;
     let storage_account_info = next_account_info(account_info_iter)?;
-    let this_program_account_info = next_account_info(account_info_iter)?;
-    let this_program_data_account_info = next_account_info(account_info_iter)?;
-    let current_upgrade_authority_info = next_account_info(account_info_iter)?;
-    let pda_upgrade_authority_info = next_account_info(account_info_iter)?;
     let sysvar_clock_account_info = next_account_info(account_info_iter)?;
-
-    let team_account_info = next_account_info(account_info_iter)?;
-    let registry_storage_account_info = next_account_info(account_info_iter)?;
-    let system_program_info = next_account_info(account_info_iter)?;
+    let _system_program_info = next_account_info(account_info_iter)?;
+    let buyback_account_info = next_account_info(account_info_iter)?;
+    let validator_info_account_info = next_account_info(account_info_iter)?;
 
     let rent_data = get_rent_data(account_info_iter, rent_is_from_account)?;
+    let clock_data = get_clock_data_from_account(sysvar_clock_account_info)?;
+    // Bundle listings also take the other vote accounts of the bundle, followed by the
+    // `self_stake_accounts` stake accounts sold along. Listings mediated by a panel end with the
+    // mediator registry.
+    let trailing_accounts = account_info_iter.as_slice();
+    let (trailing_accounts, mediator_registry_info) = match mediation_panel {
+        Some(_mediation_panel) => {
+            let (mediator_registry_info, trailing_accounts) = trailing_accounts
+                .split_last()
+                .error_log("Error @ mediator registry account is missing")?;
+            (trailing_accounts, Some(mediator_registry_info))
+        }
+        None => (trailing_accounts, None),
+    };
+    if trailing_accounts.len() < self_stake_accounts as usize {
+        Err(InglError::InvalidData.utilize("Self-stake accounts are missing"))?
+    }
+    let (bundled_vote_account_infos, self_stake_account_infos) =
+        trailing_accounts.split_at(trailing_accounts.len() - self_stake_accounts as usize);
 
-    if mediation_interval > 30 * 86400 as u32 {
-        Err(InglError::TooLate.utilize("Mediatable date can't be more than 30 days in the future"))?
+    verify_listing_details(mediation_interval, &validator_name)?;
+    let secondary_items: Vec<StoredSecondaryItem> = secondary_items
+        .iter()
+        .map(|item| item.to_stored())
+        .collect();
+    verify_secondary_items(
+        &secondary_items,
+        !bundled_vote_account_infos.is_empty(),
+        !self_stake_account_infos.is_empty(),
+    )?;
+
+    if delivery_interval == Some(0) {
+        Err(InglError::InvalidData.utilize("Delivery interval can't be zero"))?
+    }
+    let silence_interval = silence_interval.unwrap_or(DEFAULT_SILENCE_INTERVAL);
+    if silence_interval <= mediation_interval
+        || silence_interval <= delivery_interval.unwrap_or_default()
+    {
+        Err(InglError::InvalidData
+            .utilize("Silence interval must outlast the mediation and delivery intervals"))?
+    }
+    if let Some(mediation_panel) = mediation_panel {
+        if mediation_panel.threshold < MIN_MEDIATION_PANEL_THRESHOLD
+            || mediation_panel.threshold as usize > MAX_MEDIATORS
+        {
+            Err(InglError::InvalidData.utilize("Mediation panel threshold is out of bounds"))?
+        }
+        if mediation_panel.voting_interval == 0 {
+            Err(InglError::InvalidData.utilize("Mediation panel voting interval can't be zero"))?
+        }
+        let mediator_registry_info =
+            mediator_registry_info.error_log("Error @ mediator registry account is missing")?;
+        mediator_registry_info
+            .assert_seed(program_id, &[MEDIATOR_REGISTRY_SEED])
+            .error_log("Error @ mediator_registry_info.assert_seed")?;
+        let active_mediators = MediatorRegistry::parse(mediator_registry_info, program_id)?
+            .mediators
+            .iter()
+            .filter(|mediator| !mediator.suspended)
+            .count();
+        if mediation_panel.threshold as usize > active_mediators {
+            Err(InglError::InvalidData
+                .utilize("Mediation panel threshold exceeds the active registered mediators"))?
+        }
     }
 
-    if validator_name.is_empty() {
-        Err(InglError::InvalidData.utilize("Validator name can't be empty"))?
+    if let Some(expires_at) = expires_at {
+        if expires_at <= clock_data.unix_timestamp as u32 {
+            Err(InglError::TooLate.utilize("Listing expiry date must be in the future"))?
+        }
     }
 
-    log!(
-        log_level,
-        2,
-        "list_validator: verify_and_change_program_authority"
-    );
-    verify_and_change_program_authority(
+    let listing_type = listing_type.to_stored(clock_data.unix_timestamp as u32);
+    let installment_plan = installment_terms.map(|terms| terms.to_stored());
+    if let Some(installment_plan) = &installment_plan {
+        if !matches!(listing_type, StoredListingType::FixedPrice) || payment_mint.is_some() {
+            Err(InglError::InvalidData
+                .utilize("Installments are only offered on fixed price listings in lamports"))?
+        }
+        if installment_plan.down_payment > authorized_withdrawer_cost {
+            Err(InglError::InvalidData.utilize("Down payment can't exceed the listing price"))?
+        }
+        if installment_plan.installment_count == 0 || installment_plan.installment_interval == 0 {
+            Err(InglError::InvalidData.utilize("Installment count and interval can't be zero"))?
+        }
+    }
+
+    let revenue_share = revenue_share_basis_points.map(|basis_points| RevenueShare {
+        basis_points,
+        holders: Vec::new(),
+    });
+    if let Some(revenue_share) = &revenue_share {
+        if !matches!(listing_type, StoredListingType::FixedPrice)
+            || payment_mint.is_some()
+            || installment_plan.is_some()
+        {
+            Err(InglError::InvalidData.utilize(
+                "Revenue shares are only sold at a fixed price in lamports, without installments",
+            ))?
+        }
+        if !secondary_items.is_empty() {
+            Err(InglError::InvalidData
+                .utilize("Revenue share listings can't include secondary items"))?
+        }
+        if revenue_share.basis_points == 0 || revenue_share.basis_points > 10000 {
+            Err(InglError::BeyondBounds
+                .utilize("Revenue share basis points must be between 1 and 10000"))?
+        }
+    }
+
+    if let Some(buyback_clause) = &buyback_clause {
+        if !matches!(listing_type, StoredListingType::FixedPrice)
+            || payment_mint.is_some()
+            || installment_plan.is_some()
+            || revenue_share.is_some()
+        {
+            Err(InglError::InvalidData.utilize(
+                "Buyback clauses are only offered on plain fixed price listings in lamports",
+            ))?
+        }
+        if buyback_clause.duration() == 0 {
+            Err(InglError::InvalidData.utilize("Buyback clause duration can't be zero"))?
+        }
+    }
+
+    if !bundled_vote_account_infos.is_empty() {
+        if !matches!(
+            listing_type,
+            StoredListingType::FixedPrice | StoredListingType::DutchAuction(_)
+        ) || installment_plan.is_some()
+            || revenue_share.is_some()
+            || buyback_clause.is_some()
+        {
+            Err(InglError::InvalidData
+                .utilize("Bundles can only be sold outright at a fixed or decreasing price"))?
+        }
+        if bundled_vote_account_infos.len() > MAX_BUNDLED_VOTE_ACCOUNTS {
+            Err(InglError::BeyondBounds.utilize("Too many vote accounts in the bundle"))?
+        }
+    }
+    let mut bundled_vote_accounts: Vec<Pubkey> = Vec::new();
+    for bundled_vote_account_info in bundled_vote_account_infos {
+        if bundled_vote_account_info.key == vote_account_info.key
+            || bundled_vote_accounts.contains(bundled_vote_account_info.key)
+        {
+            Err(InglError::InvalidData.utilize("Bundled vote accounts must be distinct"))?
+        }
+        bundled_vote_accounts.push(*bundled_vote_account_info.key);
+    }
+
+    if !self_stake_account_infos.is_empty() {
+        if !matches!(
+            listing_type,
+            StoredListingType::FixedPrice | StoredListingType::DutchAuction(_)
+        ) || installment_plan.is_some()
+            || revenue_share.is_some()
+            || buyback_clause.is_some()
+        {
+            Err(InglError::InvalidData
+                .utilize("Self-stake can only be sold outright at a fixed or decreasing price"))?
+        }
+        if self_stake_account_infos.len() > MAX_SELF_STAKE_ACCOUNTS {
+            Err(InglError::BeyondBounds.utilize("Too many self-stake accounts"))?
+        }
+    }
+
+    let mut authorized_voters: Vec<Pubkey> = Vec::new();
+    for vote_account in std::iter::once(vote_account_info).chain(bundled_vote_account_infos) {
+        authorized_voters.push(
+            AuthorizedVoters::from_vote_account_data(&vote_account.data.borrow())
+                .and_then(|authorized_voters| {
+                    authorized_voters.get_authorized_voter(clock_data.epoch)
+                })
+                .error_log("Error @ reading the authorized voter of the vote account")?,
+        );
+    }
+
+    let first_refusal = get_first_refusal(
         program_id,
-        current_upgrade_authority_info,
-        pda_upgrade_authority_info,
-        this_program_account_info,
-        this_program_data_account_info,
+        buyback_account_info,
+        authorized_withdrawer_info,
+        vote_account_info,
+        clock_data.unix_timestamp as u32,
     )?;
 
+    match &listing_type {
+        StoredListingType::FixedPrice => (),
+        StoredListingType::EnglishAuction(auction) => {
+            if payment_mint.is_some() {
+                Err(InglError::InvalidData.utilize("Auctions can only be settled in lamports"))?
+            }
+            if auction.end_date <= clock_data.unix_timestamp as u32 {
+                Err(InglError::TooLate.utilize("Auction end date must be in the future"))?
+            }
+            if auction.min_bid_increment == 0 {
+                Err(InglError::InvalidData.utilize("Minimum bid increment can't be zero"))?
+            }
+        }
+        StoredListingType::DutchAuction(auction) => {
+            if auction.end_date <= auction.start_date {
+                Err(InglError::TooLate.utilize("Auction end date must be in the future"))?
+            }
+            if auction.floor_price > authorized_withdrawer_cost {
+                Err(InglError::InvalidData
+                    .utilize("Floor price can't be higher than the starting price"))?
+            }
+            if let PriceSchedule::Stepwise { step_interval } = auction.schedule {
+                if step_interval == 0 || step_interval > auction.end_date - auction.start_date {
+                    Err(InglError::BeyondBounds
+                        .utilize("Step interval must fit within the auction duration"))?
+                }
+            }
+        }
+        StoredListingType::Lease(lease) => {
+            if payment_mint.is_some() || !secondary_items.is_empty() {
+                Err(InglError::InvalidData
+                    .utilize("Leases are paid in lamports and can't include secondary items"))?
+            }
+            if lease.epochs == 0 {
+                Err(InglError::InvalidData.utilize("Lease duration can't be zero"))?
+            }
+        }
+    }
+
     log!(
         log_level,
         2,
         "list_validator: verify_and_change_authorized_withdrawer"
     );
-    verify_and_change_authorized_withdrawer(
-        program_id,
+    for vote_account in std::iter::once(vote_account_info).chain(bundled_vote_account_infos) {
+        verify_and_change_authorized_withdrawer(
+            program_id,
+            vote_account,
+            authorized_withdrawer_info,
+            pda_authorized_withdrawer_info,
+            sysvar_clock_account_info,
+        )?;
+    }
+    let validator_info = verify_validator_info(
+        validator_info,
+        validator_info_account_info,
         vote_account_info,
-        authorized_withdrawer_info,
-        pda_authorized_withdrawer_info,
-        sysvar_clock_account_info,
+        &validator_name,
     )?;
 
+    log!(
+        log_level,
+        2,
+        "list_validator: verify_and_change_stake_authorities"
+    );
+    let listed_vote_accounts: Vec<Pubkey> = std::iter::once(*vote_account_info.key)
+        .chain(bundled_vote_accounts.iter().copied())
+        .collect();
+    let mut self_stake: Vec<SelfStake> = Vec::new();
+    for stake_account in self_stake_account_infos {
+        if self_stake
+            .iter()
+            .any(|stake| stake.stake_account == *stake_account.key)
+        {
+            Err(InglError::InvalidData.utilize("Self-stake accounts must be distinct"))?
+        }
+        self_stake.push(verify_and_change_stake_authorities(
+            program_id,
+            stake_account,
+            &listed_vote_accounts,
+            authorized_withdrawer_info,
+            pda_authorized_withdrawer_info,
+            sysvar_clock_account_info,
+            &clock_data,
+        )?);
+    }
+
     log!(
         log_level,
         2,
@@ -98,29 +355,135 @@ pub fn list_validator(
         storage_account_info,
         authorized_withdrawer_info,
         vote_account_info,
+        bundled_vote_accounts,
+        authorized_voters,
         authorized_withdrawer_cost,
+        self_stake,
+        listing_type,
         secondary_items,
         description,
         validator_name,
         validator_logo_url,
         mediation_interval,
+        delivery_interval,
+        silence_interval,
+        mediation_panel,
+        expires_at,
+        clock_data.unix_timestamp as u32,
+        allowed_buyers,
+        payment_mint,
+        installment_plan,
+        revenue_share,
+        buyback_clause,
+        first_refusal,
+        validator_info,
         rent_data,
     )?;
 
-    let registry_program_accounts = vec![
-        authorized_withdrawer_info.clone(),
-        this_program_account_info.clone(),
-        team_account_info.clone(),
-        registry_storage_account_info.clone(),
-        // registry_program_config_account.clone(),
-        system_program_info.clone(),
-    ];
+    Ok(())
+}
 
-    log!(log_level, 2, "Initing Program Registration ... ");
-    invoke(
-        &register_program_instruction(*authorized_withdrawer_info.key, *program_id),
-        &registry_program_accounts,
-    )?;
+pub fn verify_listing_details(mediation_interval: u32, validator_name: &str) -> ProgramResult {
+    if mediation_interval > 30 * 86400 as u32 {
+        Err(InglError::TooLate.utilize("Mediatable date can't be more than 30 days in the future"))?
+    }
+
+    if validator_name.is_empty() {
+        Err(InglError::InvalidData.utilize("Validator name can't be empty"))?
+    }
+    Ok(())
+}
+
+/// Reserves the relisting to the previous seller when the lister bought the validator with a
+/// right of first refusal that is still running.
+pub fn get_first_refusal(
+    program_id: &Pubkey,
+    buyback_account: &AccountInfo,
+    lister_account: &AccountInfo,
+    vote_account: &AccountInfo,
+    date: u32,
+) -> Result<Option<FirstRefusal>, ProgramError> {
+    buyback_account
+        .assert_seed(program_id, &[BUYBACK_SEED, vote_account.key.as_ref()])
+        .error_log("Error @ buyback_account_info.assert_seed")?;
+    if buyback_account.data_is_empty() {
+        return Ok(None);
+    }
+
+    let buyback_data = Buyback::parse(buyback_account, program_id)?;
+    match buyback_data.clause {
+        BuybackClause::RightOfFirstRefusal {
+            response_period, ..
+        } if buyback_data.buyer == *lister_account.key && buyback_data.is_active(date) => {
+            Ok(Some(FirstRefusal {
+                holder: buyback_data.seller,
+                until: date.saturating_add(response_period),
+            }))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Checks `validator_name` against the validator info published by the identity of the vote
+/// account at `validator_info`. The name is left unverified when the lister passes `None`.
+pub fn verify_validator_info(
+    validator_info: Option<Pubkey>,
+    validator_info_account: &AccountInfo,
+    vote_account: &AccountInfo,
+    validator_name: &str,
+) -> Result<Option<Pubkey>, ProgramError> {
+    let validator_info_key = match validator_info {
+        Some(validator_info_key) => validator_info_key,
+        None => return Ok(None),
+    };
+    validator_info_account
+        .assert_key_match(&validator_info_key)
+        .error_log("Error @ validator_info_account.assert_key_match")?;
+    validator_info_account
+        .assert_owner(&config::program::ID)
+        .error_log("Error @ validator_info_account.assert_owner")?;
+    let validator_info =
+        ValidatorInfo::from_config_account_data(&validator_info_account.data.borrow())
+            .error_log("Error @ ValidatorInfo::from_config_account_data")?;
+    let identity = vote_account
+        .data
+        .borrow()
+        .get(4..36)
+        .and_then(|node_pubkey| Pubkey::try_from(node_pubkey).ok())
+        .error_log("Error @ reading the identity of the vote account")?;
+
+    if validator_info.identity != identity {
+        Err(InglError::InvalidData
+            .utilize("Validator info is not published by the identity of the vote account"))?
+    }
+    if validator_info.name != validator_name {
+        Err(InglError::InvalidData
+            .utilize("Validator name does not match the published validator info"))?
+    }
+    Ok(Some(*validator_info_account.key))
+}
+
+/// Built-in items are verified by the program, so a listing can only sell each of them once.
+pub fn verify_secondary_items(
+    secondary_items: &[StoredSecondaryItem],
+    is_bundle: bool,
+    has_self_stake: bool,
+) -> ProgramResult {
+    let identity_items = secondary_items
+        .iter()
+        .filter(|item| item.kind == SecondaryItemKind::ValidatorIdentity)
+        .count();
+    if identity_items > 1 {
+        Err(InglError::InvalidData.utilize("The validator identity can only be sold once"))?
+    }
+    if identity_items > 0 && is_bundle {
+        Err(InglError::InvalidData.utilize("The validator identity of a bundle can't be sold"))?
+    }
+    // The self-stake is handed over at purchase, while the identity rotation happens later.
+    if identity_items > 0 && has_self_stake {
+        Err(InglError::InvalidData
+            .utilize("The validator identity can't be sold along with self-stake"))?
+    }
     Ok(())
 }
 
@@ -129,51 +492,82 @@ pub fn create_storage_and_store_data<'a>(
     storage_account: &AccountInfo<'a>,
     payer_account: &AccountInfo<'a>,
     vote_account: &AccountInfo<'a>,
+    bundled_vote_accounts: Vec<Pubkey>,
+    authorized_voters: Vec<Pubkey>,
     cost: u64,
-    secondary_items: Vec<SecondaryItem>,
+    self_stake: Vec<SelfStake>,
+    listing_type: StoredListingType,
+    secondary_items: Vec<StoredSecondaryItem>,
     description: String,
     validator_name: String,
     validator_logo_url: String,
     mediation_interval: u32,
+    delivery_interval: Option<u32>,
+    silence_interval: u32,
+    mediation_panel: Option<MediationPanel>,
+    expires_at: Option<u32>,
+    date_listed: u32,
+    allowed_buyers: Vec<Pubkey>,
+    payment_mint: Option<Pubkey>,
+    installment_plan: Option<InstallmentPlan>,
+    revenue_share: Option<RevenueShare>,
+    buyback_clause: Option<BuybackClause>,
+    first_refusal: Option<FirstRefusal>,
+    validator_info: Option<Pubkey>,
     rent_data: Rent,
 ) -> ProgramResult {
     let (_storage_key, storage_account_bump) = storage_account
-        .assert_seed(program_id, &[PROGRAM_STORAGE_SEED])
+        .assert_seed(
+            program_id,
+            &[PROGRAM_STORAGE_SEED, vote_account.key.as_ref()],
+        )
         .error_log("Error @ storage_account_info.assert_seed")?;
 
     let storage_data = Storage {
         validation_phrase: STORAGE_VALIDATION_PHRASE,
         authorized_withdrawer: *payer_account.key,
         vote_account: *vote_account.key,
+        bundled_vote_accounts,
+        authorized_voters,
         authorized_withdrawer_cost: cost,
+        self_stake,
+        listing_type,
+        payment_mint,
         request_mediation_date: None,
         mediation_date: None,
         mediation_shares: None,
-        secondary_items: secondary_items
-            .iter()
-            .map(|item| item.to_stored())
-            .collect(),
+        secondary_items,
         description,
         validator_name,
         validator_logo_url,
         purchase: None,
         mediation_interval,
+        delivery_interval,
+        silence_interval,
+        mediation_panel,
+        expires_at,
+        date_listed,
+        allowed_buyers,
+        installment_plan,
+        revenue_share,
+        buyback_clause,
+        first_refusal,
+        validator_info,
     };
 
-    let space = storage_data.get_space();
-    let lamports = rent_data.minimum_balance(space);
-    invoke_signed(
-        &system_instruction::create_account(
-            &payer_account.key,
-            &storage_account.key,
-            lamports,
-            space as u64,
-            program_id,
-        ),
-        &[payer_account.clone(), storage_account.clone()],
-        &[&[PROGRAM_STORAGE_SEED, &[storage_account_bump]]],
+    create_pda_account_with_rent(
+        program_id,
+        payer_account,
+        storage_account,
+        storage_data.get_space(),
+        &[
+            PROGRAM_STORAGE_SEED,
+            vote_account.key.as_ref(),
+            &[storage_account_bump],
+        ],
+        &rent_data,
     )
-    .error_log("Error @ system_instruction::create_account")?;
+    .error_log("Error @ create_pda_account_with_rent")?;
 
     storage_data
         .serialize(&mut &mut storage_account.data.borrow_mut()[..])
@@ -224,53 +618,68 @@ pub fn verify_and_change_authorized_withdrawer<'a>(
     Ok(())
 }
 
-pub fn verify_and_change_program_authority<'a>(
+/// Moves the staker and withdrawer of a stake account delegated to one of the listed vote
+/// accounts from the seller to the program.
+pub fn verify_and_change_stake_authorities<'a>(
     program_id: &Pubkey,
+    stake_account: &AccountInfo<'a>,
+    listed_vote_accounts: &[Pubkey],
     current_authority: &AccountInfo<'a>,
-    pda_authority: &AccountInfo<'a>,
-    this_program: &AccountInfo,
-    this_program_data: &AccountInfo<'a>,
-) -> ProgramResult {
-    this_program
-        .assert_owner(&bpf_loader_upgradeable::id())
-        .error_log("Error @ program owner assertion")?;
-    current_authority
-        .assert_signer()
-        .error_log("Error @ current_authority.assert_signer")?;
-    this_program_data
-        .assert_seed(&bpf_loader_upgradeable::id(), &[this_program.key.as_ref()])
-        .error_log("Error @ program data key assertion")?;
+    pda_authorized_withdrawer: &AccountInfo<'a>,
+    sysvar_clock_account: &AccountInfo<'a>,
+    clock_data: &Clock,
+) -> Result<SelfStake, ProgramError> {
+    stake_account
+        .assert_owner(&stake::program::ID)
+        .error_log("stake_account must be owned by stake_program")?;
+    pda_authorized_withdrawer
+        .assert_seed(program_id, &[PDA_AUTHORIZED_WITHDRAWER_SEED])
+        .error_log("Error @ pda_authorized_withdrawer_info.assert_seed")?;
 
+    let stake_state: StakeState = bincode::deserialize(&stake_account.data.borrow())
+        .ok()
+        .error_log("Error @ stake account deserialization")?;
+    let (meta, stake) = match stake_state {
+        StakeState::Stake(meta, stake) => (meta, stake),
+        _ => Err(InglError::InvalidData.utilize("Self-stake accounts must be delegated"))?,
+    };
+    if !listed_vote_accounts.contains(&stake.delegation.voter_pubkey) {
+        Err(InglError::InvalidData
+            .utilize("Self-stake must be delegated to one of the listed vote accounts"))?
+    }
+    if stake.delegation.deactivation_epoch != Epoch::MAX {
+        Err(InglError::InvalidData.utilize("Self-stake can't be deactivating"))?
+    }
+    if meta.lockup.is_in_force(clock_data, None) {
+        Err(InglError::TooEarly.utilize("Self-stake lockup is still in force"))?
+    }
+    current_authority
+        .assert_key_match(&meta.authorized.staker)
+        .error_log("Error @ current_authority is not the staker")?;
     current_authority
-        .assert_key_match(&Box::new(
-            Pubkey::try_from(
-                &this_program_data.data.borrow()[13..45], // Upgrade authority of the program
-            )
-            .expect("can't fetch upgrade authority"),
-        ))
-        .error_log("Error @ authority key assertion")?;
-
-    this_program
-        .assert_key_match(program_id)
-        .error_log("Error @ program key assertion")?;
-
-    pda_authority
-        .assert_seed(program_id, &[PDA_UPGRADE_AUTHORITY_SEED])
-        .error_log("Error @ pda_upgrade_authority.assert_seed")?;
+        .assert_key_match(&meta.authorized.withdrawer)
+        .error_log("Error @ current_authority is not the stake withdrawer")?;
 
-    invoke(
-        &bpf_loader_upgradeable::set_upgrade_authority(
-            this_program.key,
-            current_authority.key,
-            Some(pda_authority.key),
-        ),
-        &[
-            this_program_data.clone(),
-            current_authority.clone(),
-            pda_authority.clone(),
-        ],
-    )
-    .error_log("Error setting upgrade authority")?;
+    for stake_authorize in [StakeAuthorize::Staker, StakeAuthorize::Withdrawer] {
+        invoke(
+            &stake::instruction::authorize(
+                stake_account.key,
+                current_authority.key,
+                pda_authorized_withdrawer.key,
+                stake_authorize,
+                None,
+            ),
+            &[
+                stake_account.clone(),
+                sysvar_clock_account.clone(),
+                current_authority.clone(),
+            ],
+        )
+        .error_log("Error switching stake authority")?;
+    }
 
-    Ok(())
+    Ok(SelfStake {
+        stake_account: *stake_account.key,
+        lamports: stake_account.lamports(),
+    })
 }
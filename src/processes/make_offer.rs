@@ -0,0 +1,143 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke,
+    pubkey::Pubkey,
+    system_instruction,
+};
+
+use crate::{
+    error::InglError,
+    log,
+    state::{
+        consts::{OFFER_SEED, OFFER_VALIDATION_PHRASE, PROGRAM_STORAGE_SEED},
+        LogLevel, Offer, Storage, StoredListingType,
+    },
+    utils::{create_pda_account, get_clock_data, AccountInfoHelpers, OptionExt, ResultExt},
+};
+
+/// Locks an offer for a fixed price listing in a PDA scoped to the listing and the buyer.
+pub fn make_offer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    expiry_date: u32,
+    log_level: LogLevel,
+    clock_is_from_account: bool,
+) -> ProgramResult {
+    log!(log_level, 4, "make_offer called");
+    let account_info_iter = &mut accounts.iter();
+    let buyer_account_info = next_account_info(account_info_iter)?;
+    let storage_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let offer_account_info = next_account_info(account_info_iter)?;
+    let _system_program_info = next_account_info(account_info_iter)?;
+
+    let clock_data = get_clock_data(account_info_iter, clock_is_from_account)?;
+
+    buyer_account_info
+        .assert_signer()
+        .error_log("Error @ buyer_account_info.assert_signer")?;
+
+    storage_account_info
+        .assert_seed(
+            program_id,
+            &[PROGRAM_STORAGE_SEED, vote_account_info.key.as_ref()],
+        )
+        .error_log("Error @ storage_account_info.assert_seed")?;
+    let (_offer_key, offer_bump) = offer_account_info
+        .assert_seed(
+            program_id,
+            &[
+                OFFER_SEED,
+                vote_account_info.key.as_ref(),
+                buyer_account_info.key.as_ref(),
+            ],
+        )
+        .error_log("Error @ offer_account_info.assert_seed")?;
+
+    let storage_data = Storage::parse(storage_account_info, program_id)?;
+
+    if let Some(_purchase) = storage_data.purchase {
+        Err(InglError::TooLate.utilize("validator is already bought"))?
+    }
+    if storage_data.is_expired(clock_data.unix_timestamp as u32) {
+        Err(InglError::TooLate.utilize("listing has expired"))?
+    }
+    storage_data
+        .assert_allowed_buyer(buyer_account_info.key)
+        .error_log("Error @ storage_data.assert_allowed_buyer")?;
+    storage_data
+        .assert_first_refusal(buyer_account_info.key, clock_data.unix_timestamp as u32)
+        .error_log("Error @ storage_data.assert_first_refusal")?;
+    if !matches!(storage_data.listing_type, StoredListingType::FixedPrice) {
+        Err(InglError::InvalidData.utilize("offers can only be made on fixed price listings"))?
+    }
+    if storage_data.payment_mint.is_some() {
+        Err(InglError::InvalidData.utilize("offers can only be made in lamports"))?
+    }
+    if storage_data.installment_plan.is_some()
+        || storage_data.revenue_share.is_some()
+        || storage_data.buyback_clause.is_some()
+        || !storage_data.bundled_vote_accounts.is_empty()
+        || !storage_data.self_stake.is_empty()
+    {
+        Err(InglError::InvalidData.utilize(
+            "offers can't be made on installment, revenue share, buyback, bundle or self-stake listings",
+        ))?
+    }
+    if expiry_date <= clock_data.unix_timestamp as u32 {
+        Err(InglError::TooLate.utilize("offer expiry date must be in the future"))?
+    }
+    if amount == 0 {
+        Err(InglError::InvalidData.utilize("offer amount can't be zero"))?
+    }
+
+    let offer_data = Offer {
+        validation_phrase: OFFER_VALIDATION_PHRASE,
+        buyer: *buyer_account_info.key,
+        vote_account: *vote_account_info.key,
+        amount,
+        collateral: storage_data
+            .secondary_items_cost()
+            .checked_mul(2)
+            .error_log("secondary items cost * 2 overflows")?,
+        date: clock_data.unix_timestamp as u32,
+        expiry_date,
+    };
+
+    let locked_lamports = amount
+        .checked_add(offer_data.collateral)
+        .error_log("offer amount + collateral overflows")?;
+
+    log!(log_level, 2, "make_offer: creating offer account");
+    create_pda_account(
+        program_id,
+        buyer_account_info,
+        offer_account_info,
+        Offer::get_space(),
+        &[
+            OFFER_SEED,
+            vote_account_info.key.as_ref(),
+            buyer_account_info.key.as_ref(),
+            &[offer_bump],
+        ],
+    )
+    .error_log("Error @ create_pda_account")?;
+    invoke(
+        &system_instruction::transfer(
+            buyer_account_info.key,
+            offer_account_info.key,
+            locked_lamports,
+        ),
+        &[buyer_account_info.clone(), offer_account_info.clone()],
+    )
+    .error_log("Error @ system_instruction::transfer")?;
+
+    offer_data
+        .serialize(&mut &mut offer_account_info.data.borrow_mut()[..])
+        .error_log("Error @ offer_data.serialize")?;
+
+    Ok(())
+}
@@ -1,26 +1,35 @@
 use borsh::BorshSerialize;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    clock::Clock,
     entrypoint::ProgramResult,
-    program::invoke,
+    program_error::ProgramError,
     pubkey::Pubkey,
-    system_instruction,
 };
 
 use crate::{
     error::InglError,
     log,
+    processes::{
+        claim::record_claims,
+        validate_secondary_items_transfers::{
+            release_escrow_once_validated, settle_secondary_item,
+        },
+    },
     state::{
-        consts::{ESCROW_ACCOUNT_SEED, MEDIATORS, PROGRAM_STORAGE_SEED, TEAM_ADDRESS},
-        LogLevel, MediationShares, Storage,
+        consts::{MEDIATOR_REGISTRY_SEED, PROGRAM_STORAGE_SEED, TEAM_ADDRESS},
+        Claim, LogLevel, MediationShares, MediatorRegistry, Storage,
     },
     utils::{get_clock_data, AccountInfoHelpers, OptionExt, ResultExt},
 };
 
+/// Splits what is left in escrow between the buyer, the seller and the team, or only the cost and
+/// collateral of the secondary item at `item_index`, which then counts as validated.
 pub fn mediate(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     mediation_shares: MediationShares,
+    item_index: Option<u32>,
     log_level: LogLevel,
     clock_is_from_account: bool,
 ) -> ProgramResult {
@@ -28,9 +37,13 @@ pub fn mediate(
     let payer_account_info = next_account_info(account_info_iter)?;
     let authorized_withdrawer_info = next_account_info(account_info_iter)?;
     let storage_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
     let buyer_account_info = next_account_info(account_info_iter)?;
     let escrow_account_info = next_account_info(account_info_iter)?;
     let team_account_info = next_account_info(account_info_iter)?;
+    let item_claims_account_info = next_account_info(account_info_iter)?;
+    let claims_account_info = next_account_info(account_info_iter)?;
+    let mediator_registry_info = next_account_info(account_info_iter)?;
 
     let clock_data = get_clock_data(account_info_iter, clock_is_from_account)?;
 
@@ -38,22 +51,69 @@ pub fn mediate(
         .assert_signer()
         .error_log("Error @ payer_account_info.assert_signer")?;
 
-    if !MEDIATORS.contains(payer_account_info.key) {
-        Err(InglError::NotAuthorized.utilize("only approved mediators can mediate"))?
+    mediator_registry_info
+        .assert_seed(program_id, &[MEDIATOR_REGISTRY_SEED])
+        .error_log("Error @ mediator_registry_info.assert_seed")?;
+    let mediator_registry = MediatorRegistry::parse(mediator_registry_info, program_id)?;
+    if !mediator_registry.is_active_mediator(payer_account_info.key) {
+        Err(InglError::NotAuthorized.utilize("only registered mediators can mediate"))?
+    }
+
+    let mut storage_data = parse_mediated_storage(
+        program_id,
+        storage_account_info,
+        vote_account_info,
+        authorized_withdrawer_info,
+        buyer_account_info,
+        team_account_info,
+    )?;
+    if let Some(_mediation_panel) = storage_data.mediation_panel {
+        Err(InglError::NotAuthorized
+            .utilize("disputes of this listing are decided by a mediator panel"))?
     }
 
+    apply_mediation(
+        program_id,
+        &mut storage_data,
+        mediation_shares,
+        item_index,
+        vote_account_info,
+        escrow_account_info,
+        item_claims_account_info,
+        claims_account_info,
+        payer_account_info,
+        &clock_data,
+        log_level,
+    )?;
+
+    storage_data
+        .serialize(&mut &mut storage_account_info.data.borrow_mut()[..])
+        .error_log("Error @ storage_data.serialize")?;
+
+    Ok(())
+}
+
+/// Parses the storage of a purchase under mediation, asserting the seller, buyer and team
+/// accounts the escrow is split between.
+pub fn parse_mediated_storage(
+    program_id: &Pubkey,
+    storage_account_info: &AccountInfo,
+    vote_account_info: &AccountInfo,
+    authorized_withdrawer_info: &AccountInfo,
+    buyer_account_info: &AccountInfo,
+    team_account_info: &AccountInfo,
+) -> Result<Storage, ProgramError> {
     storage_account_info
-        .assert_seed(program_id, &[PROGRAM_STORAGE_SEED])
+        .assert_seed(
+            program_id,
+            &[PROGRAM_STORAGE_SEED, vote_account_info.key.as_ref()],
+        )
         .error_log("Error @ storage_account_info.assert_seed")?;
-    escrow_account_info
-        .assert_seed(program_id, &[ESCROW_ACCOUNT_SEED])
-        .error_log("Error @ escrow_account_info.assert_seed")?;
-
     team_account_info
         .assert_key_match(&TEAM_ADDRESS)
         .error_log("Error @ team_account_info.assert_key_match")?;
 
-    let mut storage_data = Storage::parse(storage_account_info, program_id)?;
+    let storage_data = Storage::parse(storage_account_info, program_id)?;
 
     if let Some(purchase_data) = &storage_data.purchase {
         if purchase_data.date_finalized.is_some() {
@@ -66,16 +126,6 @@ pub fn mediate(
         .error_log(
             "Error @ authorized_withdrawer_info.assert_key_match(&storage_data.authorized_withdrawer)",
         )?;
-
-    match storage_data.request_mediation_date {
-        Some(_request_mediation_date) => {
-            if storage_data.mediation_date.is_some() {
-                Err(InglError::TooLate.utilize("Mediation has already taken place"))?
-            }
-        }
-        None => Err(InglError::TooEarly.utilize("Mediation has not been requested yet"))?,
-    }
-
     buyer_account_info
         .assert_key_match(
             &storage_data
@@ -85,34 +135,76 @@ pub fn mediate(
         )
         .error_log("Error @ buyer_account_info.assert_key_match(&storage_data.buyer)")?;
 
-    storage_data.mediation_date = Some(clock_data.unix_timestamp as u32);
-    storage_data
-        .purchase
-        .error_log("mediation can only take place if purchase took place")?
-        .date_finalized = Some(clock_data.unix_timestamp as u32);
+    Ok(storage_data)
+}
 
+/// Settles the mediation requested over what is left in escrow, or over the secondary item at
+/// `item_index` in its own claims account, with `mediation_shares`. The payer covers the rent of
+/// the claims accounts.
+pub fn apply_mediation<'a>(
+    program_id: &Pubkey,
+    storage_data: &mut Storage,
+    mediation_shares: MediationShares,
+    item_index: Option<u32>,
+    vote_account_info: &AccountInfo<'a>,
+    escrow_account_info: &AccountInfo<'a>,
+    item_claims_account_info: &AccountInfo<'a>,
+    claims_account_info: &AccountInfo<'a>,
+    payer_account_info: &AccountInfo<'a>,
+    clock_data: &Clock,
+    log_level: LogLevel,
+) -> ProgramResult {
     mediation_shares.verify_sum()?;
+    storage_data.assert_mediation_pending(item_index)?;
+
+    if let Some(item_index) = item_index {
+        let item = storage_data
+            .secondary_items
+            .get_mut(item_index as usize)
+            .error_log("Error @ secondary item index out of bounds")?;
+        log!(
+            log_level,
+            3,
+            "item {}: mediation_shares: {:?}",
+            item_index,
+            mediation_shares
+        );
+        item.mediation_shares = Some(mediation_shares);
+        item.date_validated = Some(clock_data.unix_timestamp as u32);
+
+        settle_secondary_item(
+            program_id,
+            storage_data,
+            item_index,
+            vote_account_info,
+            escrow_account_info,
+            item_claims_account_info,
+            payer_account_info,
+        )?;
+        return release_escrow_once_validated(
+            program_id,
+            storage_data,
+            vote_account_info,
+            escrow_account_info,
+            claims_account_info,
+            payer_account_info,
+            clock_data,
+        );
+    }
 
-    let to_buyer = escrow_account_info
-        .lamports()
-        .checked_mul(mediation_shares.buyer as u64)
-        .error_log("buyer share * escrow lamports overflows")?
-        .checked_div(100)
-        .error_log("buyer share * escrow lamports overflows / 100")?;
-    let to_seller = escrow_account_info
-        .lamports()
-        .checked_mul(mediation_shares.seller as u64)
-        .error_log("seller share * escrow lamports overflows")?
-        .checked_div(100)
-        .error_log("seller share * escrow lamports overflows / 100")?;
-    let to_team = escrow_account_info
-        .lamports()
-        .checked_sub(
-            to_buyer
-                .checked_add(to_seller)
-                .error_log("to_buyer + to_seller overflows")?,
-        )
-        .error_log("escrow lamports - (to_buyer + to_seller) overflows")?;
+    let purchase = storage_data
+        .purchase
+        .error_log("mediation can only take place if purchase took place")?;
+    storage_data.mediation_date = Some(clock_data.unix_timestamp as u32);
+    let purchase_data = storage_data
+        .purchase
+        .as_mut()
+        .error_log("mediation can only take place if purchase took place")?;
+    purchase_data.date_finalized = Some(clock_data.unix_timestamp as u32);
+    purchase_data.escrowed = 0;
+
+    // Only the amount recorded for this purchase is split, whatever else the escrow holds.
+    let (to_buyer, to_seller, to_team) = mediation_shares.split(purchase.escrowed)?;
 
     log!(
         log_level,
@@ -123,51 +215,31 @@ pub fn mediate(
         to_team
     );
 
-    let do_transfers = || -> ProgramResult {
-        invoke(
-            &system_instruction::transfer(
-                escrow_account_info.key,
-                authorized_withdrawer_info.key,
-                to_seller,
-            ),
-            &[
-                escrow_account_info.clone(),
-                authorized_withdrawer_info.clone(),
-            ],
-        )
-        .error_log("Error @ transfer to seller")?;
-
-        if to_buyer > 0 {
-            invoke(
-                &system_instruction::transfer(
-                    escrow_account_info.key,
-                    buyer_account_info.key,
-                    to_buyer,
-                ),
-                &[escrow_account_info.clone(), buyer_account_info.clone()],
-            )
-            .error_log("Error @ transfer to buyer")?;
-        }
-
-        if to_team > 0 {
-            invoke(
-                &system_instruction::transfer(
-                    escrow_account_info.key,
-                    team_account_info.key,
-                    to_team,
-                ),
-                &[escrow_account_info.clone(), team_account_info.clone()],
-            )
-            .error_log("Error @ transfer to team")?;
-        }
-        Ok(())
-    };
-
-    do_transfers().error_log("Error @ do_transfer")?;
-
-    storage_data
-        .serialize(&mut &mut storage_account_info.data.borrow_mut()[..])
-        .error_log("Error @ storage_data.serialize")?;
+    record_claims(
+        program_id,
+        &purchase,
+        None,
+        storage_data.payment_mint,
+        vote_account_info,
+        escrow_account_info,
+        claims_account_info,
+        payer_account_info,
+        &[
+            Claim {
+                claimant: purchase.buyer,
+                amount: to_buyer,
+            },
+            Claim {
+                claimant: storage_data.authorized_withdrawer,
+                amount: to_seller,
+            },
+            Claim {
+                claimant: TEAM_ADDRESS,
+                amount: to_team,
+            },
+        ],
+    )
+    .error_log("Error @ record_claims")?;
 
     Ok(())
 }
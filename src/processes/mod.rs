@@ -1,7 +1,31 @@
+pub mod accept_offer;
+pub mod add_mediator;
 pub mod buy;
+pub mod buy_revenue_share;
+pub mod cancel_offer;
+pub mod claim;
 pub mod delist;
+pub mod delist_expired;
+pub mod end_lease;
+pub mod exercise_buyback;
+pub mod expire_buyback;
+pub mod finalize;
+pub mod forfeit_installments;
 pub mod list;
+pub mod make_offer;
 pub mod mediate;
+pub mod pay_installment;
+pub mod place_bid;
+pub mod refund_undelivered_items;
+pub mod remove_mediator;
 pub mod request_mediation;
+pub mod rotate_validator_identity;
+pub mod settle_auction;
+pub mod settle_mediation;
+pub mod start_lease;
+pub mod suspend_mediator;
+pub mod update_commission;
+pub mod update_listing;
 pub mod validate_secondary_items_transfers;
-pub mod withdraw_rewards;
\ No newline at end of file
+pub mod vote_mediation;
+pub mod withdraw_rewards;
@@ -0,0 +1,186 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction,
+};
+
+use crate::{
+    error::InglError,
+    log,
+    processes::buy::{
+        change_authorized_voter, change_authorized_withdrawer, hands_over_withdrawer,
+        log_authorized_voter_change,
+    },
+    state::{
+        consts::{
+            ESCROWED_BASIS_POINTS, ESCROW_ACCOUNT_SEED, PROGRAM_STORAGE_SEED, TEAM_ADDRESS,
+            TEAM_FEES_BASIS_POINTS,
+        },
+        LogLevel, Purchase, Storage,
+    },
+    utils::{get_clock_data_from_account, AccountInfoHelpers, OptionExt, ResultExt},
+};
+
+/// Pays the next installment of an installment purchase. The authorized withdrawer is handed
+/// over to the buyer along with the last installment.
+pub fn pay_installment(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: LogLevel,
+) -> ProgramResult {
+    log!(log_level, 4, "pay_installment called");
+    let account_info_iter = &mut accounts.iter();
+    let buyer_account_info = next_account_info(account_info_iter)?;
+    let storage_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let registered_authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    let pda_authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    let escrow_account_info = next_account_info(account_info_iter)?;
+    let team_account_info = next_account_info(account_info_iter)?;
+    let sysvar_clock_account_info = next_account_info(account_info_iter)?;
+
+    let clock_data =
+        get_clock_data_from_account(sysvar_clock_account_info).error_log("Error @ clock parse")?;
+
+    buyer_account_info
+        .assert_signer()
+        .error_log("Error @ buyer_account_info.assert_signer")?;
+    storage_account_info
+        .assert_seed(
+            program_id,
+            &[PROGRAM_STORAGE_SEED, vote_account_info.key.as_ref()],
+        )
+        .error_log("Error @ storage_account_info.assert_seed")?;
+    team_account_info
+        .assert_key_match(&TEAM_ADDRESS)
+        .error_log("Error @ team_account_info.assert_key_match")?;
+
+    let mut storage_data = Storage::parse(storage_account_info, program_id)?;
+    registered_authorized_withdrawer_info
+        .assert_key_match(&storage_data.authorized_withdrawer)
+        .error_log("Error @ registered_authorized_withdrawer_info.assert_key_match")?;
+
+    let purchase = storage_data
+        .purchase
+        .error_log("Error @ validator is not bought yet")?;
+    buyer_account_info
+        .assert_key_match(&purchase.buyer)
+        .error_log("Only the buyer can pay the installments")?;
+    escrow_account_info
+        .assert_seed(
+            program_id,
+            &[
+                ESCROW_ACCOUNT_SEED,
+                vote_account_info.key.as_ref(),
+                &Purchase::escrow_seed(purchase.escrow_nonce),
+            ],
+        )
+        .error_log("Error @ escrow_account_info.assert_seed")?;
+    let installment_plan = storage_data
+        .installment_plan
+        .clone()
+        .error_log("Error @ listing has no installment plan")?;
+    if installment_plan.is_paid_in_full() {
+        Err(InglError::TooLate.utilize("all installments are already paid"))?
+    }
+    if installment_plan.is_defaulted(purchase.date, clock_data.unix_timestamp as u32) {
+        Err(InglError::TooLate.utilize("installment plan has defaulted"))?
+    }
+
+    let amount = installment_plan.next_installment_amount(storage_data.authorized_withdrawer_cost);
+    let (to_owner, to_escrow, to_team) = installment_split(&storage_data, amount)?;
+
+    log!(log_level, 2, "pay_installment: transferring installment");
+    for (destination, lamports) in [
+        (registered_authorized_withdrawer_info, to_owner),
+        (escrow_account_info, to_escrow),
+        (team_account_info, to_team),
+    ] {
+        if lamports > 0 {
+            invoke(
+                &system_instruction::transfer(buyer_account_info.key, destination.key, lamports),
+                &[buyer_account_info.clone(), destination.clone()],
+            )
+            .error_log("Error @ installment transfer")?;
+        }
+    }
+
+    let installment_plan = storage_data
+        .installment_plan
+        .as_mut()
+        .error_log("Error @ listing has no installment plan")?;
+    installment_plan.installments_paid += 1;
+    storage_data
+        .purchase
+        .as_mut()
+        .error_log("Error @ validator is not bought yet")?
+        .escrowed += to_escrow;
+
+    storage_data
+        .serialize(&mut &mut storage_account_info.data.borrow_mut()[..])
+        .error_log("Error @ storage_data.serialize")?;
+
+    if hands_over_withdrawer(&storage_data) {
+        log_authorized_voter_change(&storage_data, vote_account_info, &clock_data, log_level);
+        log!(log_level, 2, "pay_installment: change_authorized_voter");
+        change_authorized_voter(
+            program_id,
+            vote_account_info,
+            &purchase.authorized_voter,
+            pda_authorized_withdrawer_info,
+            sysvar_clock_account_info,
+        )
+        .error_log("Error @ change_authorized_voter")?;
+
+        log!(
+            log_level,
+            2,
+            "pay_installment: change_authorized_withdrawer"
+        );
+        change_authorized_withdrawer(
+            program_id,
+            vote_account_info,
+            buyer_account_info,
+            pda_authorized_withdrawer_info,
+            sysvar_clock_account_info,
+            log_level,
+        )
+        .error_log("Error @ change_authorized_withdrawer")?;
+    }
+
+    Ok(())
+}
+
+/// Returns the seller, escrow and team shares of an installment. The escrow keeps its share only
+/// while the secondary items transfer is not finalized.
+pub fn installment_split(
+    storage_data: &Storage,
+    amount: u64,
+) -> Result<(u64, u64, u64), ProgramError> {
+    let is_escrowed = !storage_data.secondary_items.is_empty()
+        && storage_data
+            .purchase
+            .is_some_and(|purchase| purchase.date_finalized.is_none());
+
+    let to_team: u64 = (amount as u128)
+        .checked_mul(TEAM_FEES_BASIS_POINTS.into())
+        .error_log("to_team mul calculation error")?
+        .checked_div(10000)
+        .error_log("to_team div calculation error")? as u64;
+    let to_escrow: u64 = if is_escrowed {
+        (amount as u128)
+            .checked_mul(ESCROWED_BASIS_POINTS.into())
+            .error_log("to_escrow mul calculation error")?
+            .checked_div(10000)
+            .error_log("to_escrow div calculation error")? as u64
+    } else {
+        0
+    };
+    let to_owner = amount - to_team - to_escrow;
+
+    Ok((to_owner, to_escrow, to_team))
+}
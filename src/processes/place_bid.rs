@@ -0,0 +1,150 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    pubkey::Pubkey,
+    system_instruction,
+};
+
+use crate::{
+    error::InglError,
+    log,
+    state::{
+        consts::{BID_ESCROW_SEED, PROGRAM_STORAGE_SEED},
+        Bid, LogLevel, Storage, StoredListingType,
+    },
+    utils::{get_clock_data, AccountInfoHelpers, OptionExt, ResultExt},
+};
+
+/// Escrows a bid on an english auction listing, refunding the previous highest bidder.
+/// When there is no standing bid, the bidder's own account is passed as the previous bidder.
+pub fn place_bid(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    log_level: LogLevel,
+    clock_is_from_account: bool,
+) -> ProgramResult {
+    log!(log_level, 4, "place_bid called");
+    let account_info_iter = &mut accounts.iter();
+    let bidder_account_info = next_account_info(account_info_iter)?;
+    let storage_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let bid_escrow_account_info = next_account_info(account_info_iter)?;
+    let previous_bidder_account_info = next_account_info(account_info_iter)?;
+    let _system_program_info = next_account_info(account_info_iter)?;
+
+    let clock_data = get_clock_data(account_info_iter, clock_is_from_account)?;
+
+    bidder_account_info
+        .assert_signer()
+        .error_log("Error @ bidder_account_info.assert_signer")?;
+
+    storage_account_info
+        .assert_seed(
+            program_id,
+            &[PROGRAM_STORAGE_SEED, vote_account_info.key.as_ref()],
+        )
+        .error_log("Error @ storage_account_info.assert_seed")?;
+    let (_bid_escrow_key, bid_escrow_bump) = bid_escrow_account_info
+        .assert_seed(
+            program_id,
+            &[BID_ESCROW_SEED, vote_account_info.key.as_ref()],
+        )
+        .error_log("Error @ bid_escrow_account_info.assert_seed")?;
+
+    let mut storage_data = Storage::parse(storage_account_info, program_id)?;
+
+    if let Some(_purchase) = storage_data.purchase {
+        Err(InglError::TooLate.utilize("validator is already bought"))?
+    }
+    if storage_data.is_expired(clock_data.unix_timestamp as u32) {
+        Err(InglError::TooLate.utilize("listing has expired"))?
+    }
+    storage_data
+        .assert_allowed_buyer(bidder_account_info.key)
+        .error_log("Error @ storage_data.assert_allowed_buyer")?;
+    storage_data
+        .assert_first_refusal(bidder_account_info.key, clock_data.unix_timestamp as u32)
+        .error_log("Error @ storage_data.assert_first_refusal")?;
+
+    // Bidders also escrow the secondary items collateral that a buyer would pay.
+    let collateral = storage_data
+        .secondary_items_cost()
+        .checked_mul(2)
+        .error_log("secondary items cost * 2 overflows")?;
+    let starting_price = storage_data.authorized_withdrawer_cost;
+    let auction = match &mut storage_data.listing_type {
+        StoredListingType::EnglishAuction(auction) => auction,
+        _ => Err(InglError::InvalidData.utilize("listing is not an english auction"))?,
+    };
+
+    if clock_data.unix_timestamp as u32 >= auction.end_date {
+        Err(InglError::TooLate.utilize("auction has already ended"))?
+    }
+
+    let minimum_bid = match &auction.highest_bid {
+        Some(bid) => bid
+            .amount
+            .checked_add(auction.min_bid_increment)
+            .error_log("highest bid + min bid increment overflows")?,
+        None => starting_price,
+    };
+    if amount < minimum_bid {
+        Err(InglError::InvalidData.utilize("bid is lower than the minimum accepted bid"))?
+    }
+
+    log!(log_level, 2, "place_bid: escrowing bid");
+    invoke(
+        &system_instruction::transfer(
+            bidder_account_info.key,
+            bid_escrow_account_info.key,
+            amount
+                .checked_add(collateral)
+                .error_log("bid + collateral overflows")?,
+        ),
+        &[bidder_account_info.clone(), bid_escrow_account_info.clone()],
+    )
+    .error_log("Error @ transfer to bid escrow")?;
+
+    if let Some(previous_bid) = auction.highest_bid {
+        previous_bidder_account_info
+            .assert_key_match(&previous_bid.bidder)
+            .error_log("Error @ previous_bidder_account_info.assert_key_match")?;
+
+        log!(log_level, 2, "place_bid: refunding previous bidder");
+        invoke_signed(
+            &system_instruction::transfer(
+                bid_escrow_account_info.key,
+                previous_bidder_account_info.key,
+                previous_bid
+                    .amount
+                    .checked_add(collateral)
+                    .error_log("previous bid + collateral overflows")?,
+            ),
+            &[
+                bid_escrow_account_info.clone(),
+                previous_bidder_account_info.clone(),
+            ],
+            &[&[
+                BID_ESCROW_SEED,
+                vote_account_info.key.as_ref(),
+                &[bid_escrow_bump],
+            ]],
+        )
+        .error_log("Error @ refund to previous bidder")?;
+    }
+
+    auction.highest_bid = Some(Bid {
+        bidder: *bidder_account_info.key,
+        amount,
+        date: clock_data.unix_timestamp as u32,
+    });
+
+    storage_data
+        .serialize(&mut &mut storage_account_info.data.borrow_mut()[..])
+        .error_log("Error @ storage_data.serialize")?;
+
+    Ok(())
+}
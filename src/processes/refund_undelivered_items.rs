@@ -0,0 +1,120 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    error::InglError,
+    log,
+    processes::claim::record_claims,
+    state::{consts::PROGRAM_STORAGE_SEED, Claim, LogLevel, SecondaryItemKind, Storage},
+    utils::{get_clock_data_from_account, AccountInfoHelpers, OptionExt, ResultExt},
+};
+
+/// Lets the buyer finalize a purchase whose secondary items were not all delivered before the
+/// delivery deadline, without a mediator. The buyer can claim back the undelivered items refund
+/// and the seller the rest of the escrow.
+pub fn refund_undelivered_items(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: LogLevel,
+) -> ProgramResult {
+    log!(log_level, 4, "refund_undelivered_items called");
+    let account_info_iter = &mut accounts.iter();
+    let buyer_account_info = next_account_info(account_info_iter)?;
+    let storage_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let escrow_account_info = next_account_info(account_info_iter)?;
+    let claims_account_info = next_account_info(account_info_iter)?;
+    let sysvar_clock_account_info = next_account_info(account_info_iter)?;
+
+    let clock_data =
+        get_clock_data_from_account(sysvar_clock_account_info).error_log("Error @ clock parse")?;
+
+    buyer_account_info
+        .assert_signer()
+        .error_log("Error @ buyer_account_info.assert_signer")?;
+    storage_account_info
+        .assert_seed(
+            program_id,
+            &[PROGRAM_STORAGE_SEED, vote_account_info.key.as_ref()],
+        )
+        .error_log("Error @ storage_account_info.assert_seed")?;
+
+    let mut storage_data = Storage::parse(storage_account_info, program_id)?;
+    let purchase = storage_data
+        .purchase
+        .error_log("Error @ validator is not bought yet")?;
+    buyer_account_info
+        .assert_key_match(&purchase.buyer)
+        .error_log("Only the buyer can get undelivered items refunded")?;
+    if let Some(_date_finalized) = purchase.date_finalized {
+        Err(InglError::TooLate.utilize("Purchase has already been finalized"))?
+    }
+
+    let delivery_interval = storage_data
+        .delivery_interval
+        .error_log("Error @ listing has no delivery deadline")?;
+    if (clock_data.unix_timestamp as u32) <= purchase.date.saturating_add(delivery_interval) {
+        Err(InglError::TooEarly.utilize("delivery deadline has not passed yet"))?
+    }
+    if !storage_data
+        .secondary_items
+        .iter()
+        .any(|item| item.kind == SecondaryItemKind::Custom && item.date_validated.is_none())
+    {
+        Err(InglError::InvalidData.utilize("every secondary item was delivered"))?
+    }
+    if (storage_data.request_mediation_date.is_some() && storage_data.mediation_date.is_none())
+        || storage_data.has_pending_item_dispute()
+    {
+        Err(InglError::TooEarly.utilize("a pending mediation must be resolved first"))?
+    }
+
+    let to_buyer = storage_data
+        .undelivered_items_refund()
+        .min(purchase.escrowed);
+    log!(
+        log_level,
+        3,
+        "to_buyer: {}, to_seller: {}",
+        to_buyer,
+        purchase.escrowed - to_buyer
+    );
+    record_claims(
+        program_id,
+        &purchase,
+        None,
+        storage_data.payment_mint,
+        vote_account_info,
+        escrow_account_info,
+        claims_account_info,
+        buyer_account_info,
+        &[
+            Claim {
+                claimant: purchase.buyer,
+                amount: to_buyer,
+            },
+            Claim {
+                claimant: storage_data.authorized_withdrawer,
+                amount: purchase.escrowed - to_buyer,
+            },
+        ],
+    )
+    .error_log("Error @ record_claims")?;
+
+    let purchase = storage_data
+        .purchase
+        .as_mut()
+        .error_log("Error @ validator is not bought yet")?;
+    purchase.date_finalized = Some(clock_data.unix_timestamp as u32);
+    purchase.escrowed = 0;
+
+    storage_data
+        .serialize(&mut &mut storage_account_info.data.borrow_mut()[..])
+        .error_log("Error @ storage_data.serialize")?;
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    log,
+    processes::add_mediator::store_mediator_registry,
+    state::{
+        consts::{MEDIATOR_REGISTRY_SEED, TEAM_ADDRESS},
+        LogLevel, MediatorRegistry,
+    },
+    utils::{AccountInfoHelpers, OptionExt, ResultExt},
+};
+
+/// Removes a mediator from the registry, refunding the freed rent to the team.
+pub fn remove_mediator(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: LogLevel,
+    mediator: Pubkey,
+) -> ProgramResult {
+    log!(log_level, 4, "remove_mediator called");
+    let account_info_iter = &mut accounts.iter();
+    let team_account_info = next_account_info(account_info_iter)?;
+    let registry_account_info = next_account_info(account_info_iter)?;
+
+    team_account_info
+        .assert_signer()
+        .error_log("Error @ team_account_info.assert_signer")?;
+    team_account_info
+        .assert_key_match(&TEAM_ADDRESS)
+        .error_log("Only the team can manage mediators")?;
+    registry_account_info
+        .assert_seed(program_id, &[MEDIATOR_REGISTRY_SEED])
+        .error_log("Error @ registry_account_info.assert_seed")?;
+
+    let mut registry_data = MediatorRegistry::parse(registry_account_info, program_id)?;
+    let index = registry_data
+        .mediators
+        .iter()
+        .position(|registered| registered.key == mediator)
+        .error_log("Error @ mediator is not registered")?;
+    registry_data.mediators.remove(index);
+
+    store_mediator_registry(team_account_info, registry_account_info, &registry_data)
+}
@@ -7,26 +7,33 @@ use solana_program::{
 
 use crate::{
     error::InglError,
-    state::{consts::PROGRAM_STORAGE_SEED, LogLevel, Storage},
+    state::{consts::PROGRAM_STORAGE_SEED, LogLevel, SecondaryItemKind, Storage},
     utils::{get_clock_data, AccountInfoHelpers, OptionExt, ResultExt},
 };
 
+/// Requests mediation over the whole escrow, or over a single secondary item when `item_index` is
+/// set, in which case the other items keep settling normally.
 pub fn request_mediation(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     _log_level: LogLevel,
+    item_index: Option<u32>,
     clock_is_from_account: bool,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let payer_account_info = next_account_info(account_info_iter)?;
     let storage_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
 
     let clock_data = get_clock_data(account_info_iter, clock_is_from_account)?;
 
     payer_account_info.assert_signer()?;
 
     storage_account_info
-        .assert_seed(program_id, &[PROGRAM_STORAGE_SEED])
+        .assert_seed(
+            program_id,
+            &[PROGRAM_STORAGE_SEED, vote_account_info.key.as_ref()],
+        )
         .error_log("Error @ storage pda validation")?;
 
     let mut storage_data = Storage::parse(storage_account_info, program_id)?;
@@ -45,12 +52,36 @@ pub fn request_mediation(
         Err(InglError::TooEarly.utilize("Mediation cannot be requested yet"))?
     }
 
-    if let Some(_request_mediation_date) = storage_data.request_mediation_date {
-        Err(InglError::TooLate.utilize("Mediation has already been requested"))?
+    match item_index {
+        Some(item_index) => {
+            if purchase_data.date_finalized.is_some() {
+                Err(InglError::TooLate.utilize("Purchase has already been finalized"))?
+            }
+            let item = storage_data
+                .secondary_items
+                .get_mut(item_index as usize)
+                .error_log("Error @ secondary item index out of bounds")?;
+            if item.kind != SecondaryItemKind::Custom {
+                Err(InglError::InvalidData
+                    .utilize("Built-in secondary items are validated by the program"))?
+            }
+            if let Some(_date_validated) = item.date_validated {
+                Err(InglError::TooLate.utilize("Secondary item has already been validated"))?
+            }
+            if let Some(_request_mediation_date) = item.request_mediation_date {
+                Err(InglError::TooLate
+                    .utilize("Mediation has already been requested for this item"))?
+            }
+            item.request_mediation_date = Some(clock_data.unix_timestamp as u32);
+        }
+        None => {
+            if let Some(_request_mediation_date) = storage_data.request_mediation_date {
+                Err(InglError::TooLate.utilize("Mediation has already been requested"))?
+            }
+            storage_data.request_mediation_date = Some(clock_data.unix_timestamp as u32);
+        }
     }
 
-    storage_data.request_mediation_date = Some(clock_data.unix_timestamp as u32);
-
     storage_data.serialize(&mut &mut storage_account_info.data.borrow_mut()[..])?;
 
     Ok(())
@@ -0,0 +1,171 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    vote,
+};
+
+use crate::{
+    error::InglError,
+    log,
+    processes::{
+        buy::{
+            change_authorized_voter, change_authorized_withdrawer, hands_over_withdrawer,
+            log_authorized_voter_change,
+        },
+        validate_secondary_items_transfers::{
+            release_escrow_once_validated, settle_secondary_item,
+        },
+    },
+    state::{
+        consts::{PDA_AUTHORIZED_WITHDRAWER_SEED, PROGRAM_STORAGE_SEED},
+        LogLevel, SecondaryItemKind, Storage,
+    },
+    utils::{get_clock_data_from_account, AccountInfoHelpers, OptionExt, ResultExt},
+};
+
+/// Rotates the validator identity of a bought vote account to the identity supplied by the
+/// buyer, validating the identity secondary item on chain. The program signs as authorized
+/// withdrawer, then hands the withdrawer over if nothing else holds it back.
+pub fn rotate_validator_identity(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: LogLevel,
+) -> ProgramResult {
+    log!(log_level, 4, "rotate_validator_identity called");
+    let account_info_iter = &mut accounts.iter();
+    let buyer_account_info = next_account_info(account_info_iter)?;
+    let storage_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let new_identity_info = next_account_info(account_info_iter)?;
+    let pda_authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    let escrow_account_info = next_account_info(account_info_iter)?;
+    let authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    let item_claims_account_info = next_account_info(account_info_iter)?;
+    let claims_account_info = next_account_info(account_info_iter)?;
+    let sysvar_clock_account_info = next_account_info(account_info_iter)?;
+
+    let clock_data =
+        get_clock_data_from_account(sysvar_clock_account_info).error_log("Error @ clock parse")?;
+
+    buyer_account_info
+        .assert_signer()
+        .error_log("Error @ buyer_account_info.assert_signer")?;
+    new_identity_info
+        .assert_signer()
+        .error_log("Error @ new_identity_info.assert_signer")?;
+    storage_account_info
+        .assert_seed(
+            program_id,
+            &[PROGRAM_STORAGE_SEED, vote_account_info.key.as_ref()],
+        )
+        .error_log("Error @ storage_account_info.assert_seed")?;
+    let (_pda_authorized_withdrawer_key, pda_aw_bump) = pda_authorized_withdrawer_info
+        .assert_seed(program_id, &[PDA_AUTHORIZED_WITHDRAWER_SEED])
+        .error_log("Error @ pda_authorized_withdrawer_info.assert_seed")?;
+    vote_account_info
+        .assert_owner(&vote::program::id())
+        .error_log("Error @ vote_account_info.assert_owner")?;
+
+    let mut storage_data = Storage::parse(storage_account_info, program_id)?;
+    authorized_withdrawer_info
+        .assert_key_match(&storage_data.authorized_withdrawer)
+        .error_log("Error @ authorized_withdrawer_info.assert_key_match")?;
+
+    let purchase = storage_data
+        .purchase
+        .error_log("Error @ validator is not bought yet")?;
+    buyer_account_info
+        .assert_key_match(&purchase.buyer)
+        .error_log("Only the buyer can rotate the validator identity")?;
+
+    let item_index = storage_data
+        .secondary_items
+        .iter()
+        .position(|item| item.kind == SecondaryItemKind::ValidatorIdentity)
+        .error_log("Error @ listing does not sell the validator identity")?;
+    let identity_item = &mut storage_data.secondary_items[item_index];
+    if let Some(_date_validated) = identity_item.date_validated {
+        Err(InglError::TooLate.utilize("validator identity has already been rotated"))?
+    }
+    identity_item.date_validated = Some(clock_data.unix_timestamp as u32);
+
+    log!(
+        log_level,
+        2,
+        "rotate_validator_identity: update_validator_identity"
+    );
+    invoke_signed(
+        &vote::instruction::update_validator_identity(
+            vote_account_info.key,
+            pda_authorized_withdrawer_info.key,
+            new_identity_info.key,
+        ),
+        &[
+            vote_account_info.clone(),
+            new_identity_info.clone(),
+            pda_authorized_withdrawer_info.clone(),
+        ],
+        &[&[PDA_AUTHORIZED_WITHDRAWER_SEED, &[pda_aw_bump]]],
+    )
+    .error_log("Error @ vote::instruction::update_validator_identity")?;
+
+    settle_secondary_item(
+        program_id,
+        &mut storage_data,
+        item_index as u32,
+        vote_account_info,
+        escrow_account_info,
+        item_claims_account_info,
+        buyer_account_info,
+    )?;
+    release_escrow_once_validated(
+        program_id,
+        &mut storage_data,
+        vote_account_info,
+        escrow_account_info,
+        claims_account_info,
+        buyer_account_info,
+        &clock_data,
+    )?;
+
+    storage_data
+        .serialize(&mut &mut storage_account_info.data.borrow_mut()[..])
+        .error_log("Error @ storage_data.serialize")?;
+
+    if hands_over_withdrawer(&storage_data) {
+        log_authorized_voter_change(&storage_data, vote_account_info, &clock_data, log_level);
+        log!(
+            log_level,
+            2,
+            "rotate_validator_identity: change_authorized_voter"
+        );
+        change_authorized_voter(
+            program_id,
+            vote_account_info,
+            &purchase.authorized_voter,
+            pda_authorized_withdrawer_info,
+            sysvar_clock_account_info,
+        )
+        .error_log("Error @ change_authorized_voter")?;
+
+        log!(
+            log_level,
+            2,
+            "rotate_validator_identity: change_authorized_withdrawer"
+        );
+        change_authorized_withdrawer(
+            program_id,
+            vote_account_info,
+            buyer_account_info,
+            pda_authorized_withdrawer_info,
+            sysvar_clock_account_info,
+            log_level,
+        )
+        .error_log("Error @ change_authorized_withdrawer")?;
+    }
+
+    Ok(())
+}
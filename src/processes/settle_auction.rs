@@ -0,0 +1,175 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+};
+
+use crate::{
+    error::InglError,
+    log,
+    processes::buy::{
+        change_authorized_voter, change_authorized_withdrawer, hands_over_withdrawer,
+        log_authorized_voter_change, record_purchase, sale_proceeds_split, transfer_sale_proceeds,
+    },
+    state::{
+        consts::{BID_ESCROW_SEED, ESCROW_ACCOUNT_SEED, PROGRAM_STORAGE_SEED, TEAM_ADDRESS},
+        LogLevel, Purchase, Storage, StoredListingType,
+    },
+    utils::{get_clock_data_from_account, AccountInfoHelpers, OptionExt, ResultExt},
+};
+
+/// Closes an ended english auction. If the reserve price is met, the highest bidder buys
+/// the validator, otherwise their bid is refunded and the seller may delist.
+pub fn settle_auction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: LogLevel,
+) -> ProgramResult {
+    log!(log_level, 4, "settle_auction called");
+    let account_info_iter = &mut accounts.iter();
+    let storage_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let bid_escrow_account_info = next_account_info(account_info_iter)?;
+    let registered_authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    let winner_account_info = next_account_info(account_info_iter)?;
+    let pda_authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    let escrow_account_info = next_account_info(account_info_iter)?;
+    let team_account_info = next_account_info(account_info_iter)?;
+    let sysvar_clock_account_info = next_account_info(account_info_iter)?;
+
+    let clock_data =
+        get_clock_data_from_account(sysvar_clock_account_info).error_log("Error @ clock parse")?;
+
+    storage_account_info
+        .assert_seed(
+            program_id,
+            &[PROGRAM_STORAGE_SEED, vote_account_info.key.as_ref()],
+        )
+        .error_log("Error @ storage_account_info.assert_seed")?;
+    let (_bid_escrow_key, bid_escrow_bump) = bid_escrow_account_info
+        .assert_seed(
+            program_id,
+            &[BID_ESCROW_SEED, vote_account_info.key.as_ref()],
+        )
+        .error_log("Error @ bid_escrow_account_info.assert_seed")?;
+    team_account_info
+        .assert_key_match(&TEAM_ADDRESS)
+        .error_log("Error @ team_account_info.assert_key_match")?;
+
+    let mut storage_data = Storage::parse(storage_account_info, program_id)?;
+    escrow_account_info
+        .assert_seed(
+            program_id,
+            &[
+                ESCROW_ACCOUNT_SEED,
+                vote_account_info.key.as_ref(),
+                &Purchase::escrow_seed(storage_data.date_listed),
+            ],
+        )
+        .error_log("Error @ escrow_account_info.assert_seed")?;
+    registered_authorized_withdrawer_info
+        .assert_key_match(&storage_data.authorized_withdrawer)
+        .error_log("Error @ registered_authorized_withdrawer_info.assert_key_match")?;
+
+    if let Some(_purchase) = storage_data.purchase {
+        Err(InglError::TooLate.utilize("validator is already bought"))?
+    }
+
+    let auction = match &storage_data.listing_type {
+        StoredListingType::EnglishAuction(auction) => auction.clone(),
+        _ => Err(InglError::InvalidData.utilize("listing is not an english auction"))?,
+    };
+    if (clock_data.unix_timestamp as u32) < auction.end_date {
+        Err(InglError::TooEarly.utilize("auction has not ended yet"))?
+    }
+
+    let winning_bid = auction
+        .highest_bid
+        .error_log("auction received no bids, the seller may delist")?;
+    winner_account_info
+        .assert_key_match(&winning_bid.bidder)
+        .error_log("Error @ winner_account_info.assert_key_match")?;
+
+    let bid_escrow_seeds: &[&[u8]] = &[
+        BID_ESCROW_SEED,
+        vote_account_info.key.as_ref(),
+        &[bid_escrow_bump],
+    ];
+    let reserve_met = match auction.reserve_price {
+        Some(reserve_price) => winning_bid.amount >= reserve_price,
+        None => true,
+    };
+
+    if reserve_met {
+        log!(log_level, 2, "settle_auction: transfer_sale_proceeds");
+        transfer_sale_proceeds(
+            bid_escrow_account_info,
+            &[bid_escrow_seeds],
+            &storage_data,
+            winning_bid.amount,
+            registered_authorized_withdrawer_info,
+            escrow_account_info,
+            team_account_info,
+        )
+        .error_log("Error @ transfer_sale_proceeds")?;
+        let (_to_owner, to_escrow, _to_team) =
+            sale_proceeds_split(&storage_data, winning_bid.amount)?;
+        record_purchase(
+            &mut storage_data,
+            &winning_bid.bidder,
+            &winning_bid.bidder,
+            to_escrow,
+            &clock_data,
+        );
+    } else if let StoredListingType::EnglishAuction(auction) = &mut storage_data.listing_type {
+        auction.highest_bid = None;
+    }
+
+    // Whatever is left, the whole bid when the reserve is not met, goes back to the bidder.
+    let remaining_lamports = bid_escrow_account_info.lamports();
+    if remaining_lamports > 0 {
+        invoke_signed(
+            &system_instruction::transfer(
+                bid_escrow_account_info.key,
+                winner_account_info.key,
+                remaining_lamports,
+            ),
+            &[bid_escrow_account_info.clone(), winner_account_info.clone()],
+            &[bid_escrow_seeds],
+        )
+        .error_log("Error @ refund to bidder")?;
+    }
+
+    storage_data
+        .serialize(&mut &mut storage_account_info.data.borrow_mut()[..])
+        .error_log("Error @ storage_data.serialize")?;
+
+    if reserve_met && hands_over_withdrawer(&storage_data) {
+        log_authorized_voter_change(&storage_data, vote_account_info, &clock_data, log_level);
+        log!(log_level, 2, "settle_auction: change_authorized_voter");
+        change_authorized_voter(
+            program_id,
+            vote_account_info,
+            winner_account_info.key,
+            pda_authorized_withdrawer_info,
+            sysvar_clock_account_info,
+        )
+        .error_log("Error @ change_authorized_voter")?;
+
+        log!(log_level, 2, "settle_auction: change_authorized_withdrawer");
+        change_authorized_withdrawer(
+            program_id,
+            vote_account_info,
+            winner_account_info,
+            pda_authorized_withdrawer_info,
+            sysvar_clock_account_info,
+            log_level,
+        )
+        .error_log("Error @ change_authorized_withdrawer")?;
+    }
+
+    Ok(())
+}
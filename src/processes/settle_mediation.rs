@@ -0,0 +1,112 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    error::InglError,
+    log,
+    processes::mediate::{apply_mediation, parse_mediated_storage},
+    state::{consts::MEDIATION_CASE_SEED, LogLevel, MediationCase, Purchase},
+    utils::{get_clock_data_from_account, AccountInfoHelpers, OptionExt, ResultExt},
+};
+
+/// Permissionless crank that settles a dispute decided by a mediator panel, once enough
+/// mediators agree or with the median proposal of a quorum past the voting deadline. The caller
+/// covers the rent of the claims accounts.
+pub fn settle_mediation(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: LogLevel,
+    item_index: Option<u32>,
+) -> ProgramResult {
+    log!(log_level, 4, "settle_mediation called");
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    let storage_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let buyer_account_info = next_account_info(account_info_iter)?;
+    let escrow_account_info = next_account_info(account_info_iter)?;
+    let team_account_info = next_account_info(account_info_iter)?;
+    let item_claims_account_info = next_account_info(account_info_iter)?;
+    let claims_account_info = next_account_info(account_info_iter)?;
+    let mediation_case_info = next_account_info(account_info_iter)?;
+    let sysvar_clock_account_info = next_account_info(account_info_iter)?;
+
+    let clock_data =
+        get_clock_data_from_account(sysvar_clock_account_info).error_log("Error @ clock parse")?;
+
+    payer_account_info
+        .assert_signer()
+        .error_log("Error @ payer_account_info.assert_signer")?;
+
+    let mut storage_data = parse_mediated_storage(
+        program_id,
+        storage_account_info,
+        vote_account_info,
+        authorized_withdrawer_info,
+        buyer_account_info,
+        team_account_info,
+    )?;
+    let mediation_panel = storage_data
+        .mediation_panel
+        .error_log("Error @ listing is not mediated by a panel")?;
+    let purchase = storage_data
+        .purchase
+        .error_log("mediation can only take place if purchase took place")?;
+
+    mediation_case_info
+        .assert_seed(
+            program_id,
+            &[
+                MEDIATION_CASE_SEED,
+                vote_account_info.key.as_ref(),
+                &Purchase::escrow_seed(purchase.escrow_nonce),
+                &Purchase::item_seed(item_index),
+            ],
+        )
+        .error_log("Error @ mediation_case_info.assert_seed")?;
+    let mut mediation_case = MediationCase::parse(mediation_case_info, program_id)?;
+    if let Some(_date_settled) = mediation_case.date_settled {
+        Err(InglError::TooLate.utilize("Mediation case has already been settled"))?
+    }
+    let mediation_shares =
+        match mediation_case.decision(&mediation_panel, clock_data.unix_timestamp as u32) {
+            Some(mediation_shares) => mediation_shares,
+            None => Err(InglError::TooEarly.utilize("mediator panel has not decided yet"))?,
+        };
+    log!(
+        log_level,
+        3,
+        "settle_mediation: {} votes, mediation_shares: {:?}",
+        mediation_case.votes.len(),
+        mediation_shares
+    );
+
+    apply_mediation(
+        program_id,
+        &mut storage_data,
+        mediation_shares,
+        item_index,
+        vote_account_info,
+        escrow_account_info,
+        item_claims_account_info,
+        claims_account_info,
+        payer_account_info,
+        &clock_data,
+        log_level,
+    )?;
+
+    mediation_case.date_settled = Some(clock_data.unix_timestamp as u32);
+    mediation_case
+        .serialize(&mut &mut mediation_case_info.data.borrow_mut()[..])
+        .error_log("Error @ mediation_case.serialize")?;
+    storage_data
+        .serialize(&mut &mut storage_account_info.data.borrow_mut()[..])
+        .error_log("Error @ storage_data.serialize")?;
+
+    Ok(())
+}
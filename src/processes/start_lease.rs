@@ -0,0 +1,125 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke,
+    pubkey::Pubkey,
+    system_instruction, vote,
+};
+
+use crate::{
+    error::InglError,
+    log,
+    processes::{buy::sale_proceeds_split, withdraw_rewards::withdraw_vote_rewards},
+    state::{
+        consts::{PDA_AUTHORIZED_WITHDRAWER_SEED, PROGRAM_STORAGE_SEED, TEAM_ADDRESS},
+        LogLevel, Storage, StoredListingType,
+    },
+    utils::{get_clock_data_from_account, AccountInfoHelpers, ResultExt},
+};
+
+/// Pays for a lease listing. The rewards accrued so far are withdrawn to the lessor, then the
+/// lessee receives the vote rewards credited until the lease ends, while the program keeps the
+/// authorized withdrawer on behalf of the lessor.
+pub fn start_lease(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: LogLevel,
+) -> ProgramResult {
+    log!(log_level, 4, "start_lease called");
+    let account_info_iter = &mut accounts.iter();
+    let lessee_account_info = next_account_info(account_info_iter)?;
+    let storage_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let registered_authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    let team_account_info = next_account_info(account_info_iter)?;
+    let sysvar_clock_account_info = next_account_info(account_info_iter)?;
+    let pda_authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    // Revenue share holders and the other vote accounts of a bundle follow, as in
+    // `WithdrawRewards`.
+
+    let clock_data =
+        get_clock_data_from_account(sysvar_clock_account_info).error_log("Error @ clock parse")?;
+
+    lessee_account_info
+        .assert_signer()
+        .error_log("Error @ lessee_account_info.assert_signer")?;
+    storage_account_info
+        .assert_seed(
+            program_id,
+            &[PROGRAM_STORAGE_SEED, vote_account_info.key.as_ref()],
+        )
+        .error_log("Error @ storage_account_info.assert_seed")?;
+    team_account_info
+        .assert_key_match(&TEAM_ADDRESS)
+        .error_log("Error @ team_account_info.assert_key_match")?;
+    let (_pda_authorized_withdrawer_key, pda_aw_bump) = pda_authorized_withdrawer_info
+        .assert_seed(program_id, &[PDA_AUTHORIZED_WITHDRAWER_SEED])
+        .error_log("Error @ pda_authorized_withdrawer_info.assert_seed")?;
+    vote_account_info
+        .assert_owner(&vote::program::id())
+        .error_log("Error @ vote_account_info.assert_owner")?;
+
+    let mut storage_data = Storage::parse(storage_account_info, program_id)?;
+    registered_authorized_withdrawer_info
+        .assert_key_match(&storage_data.authorized_withdrawer)
+        .error_log("Error @ registered_authorized_withdrawer_info.assert_key_match")?;
+
+    if storage_data.is_expired(clock_data.unix_timestamp as u32) {
+        Err(InglError::TooLate.utilize("listing has expired"))?
+    }
+    storage_data
+        .assert_allowed_buyer(lessee_account_info.key)
+        .error_log("Error @ storage_data.assert_allowed_buyer")?;
+
+    match &storage_data.listing_type {
+        StoredListingType::Lease(lease) if lease.lessee.is_some() => {
+            Err(InglError::TooLate.utilize("validator is already leased"))?
+        }
+        StoredListingType::Lease(_) => (),
+        _ => Err(InglError::InvalidData.utilize("listing is not a lease"))?,
+    }
+
+    log!(
+        log_level,
+        2,
+        "start_lease: withdrawing the lessor's rewards"
+    );
+    withdraw_vote_rewards(
+        &storage_data,
+        vote_account_info,
+        registered_authorized_withdrawer_info,
+        pda_authorized_withdrawer_info,
+        pda_aw_bump,
+        account_info_iter,
+    )
+    .error_log("Error @ withdraw_vote_rewards")?;
+
+    let (to_owner, _to_escrow, to_team) =
+        sale_proceeds_split(&storage_data, storage_data.authorized_withdrawer_cost)?;
+
+    if let StoredListingType::Lease(lease) = &mut storage_data.listing_type {
+        lease.lessee = Some(*lessee_account_info.key);
+        lease.start_epoch = clock_data.epoch;
+    }
+
+    log!(log_level, 2, "start_lease: transferring lease payment");
+    for (destination, lamports) in [
+        (registered_authorized_withdrawer_info, to_owner),
+        (team_account_info, to_team),
+    ] {
+        if lamports > 0 {
+            invoke(
+                &system_instruction::transfer(lessee_account_info.key, destination.key, lamports),
+                &[lessee_account_info.clone(), destination.clone()],
+            )
+            .error_log("Error @ lease payment")?;
+        }
+    }
+
+    storage_data
+        .serialize(&mut &mut storage_account_info.data.borrow_mut()[..])
+        .error_log("Error @ storage_data.serialize")?;
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    log,
+    state::{
+        consts::{MEDIATOR_REGISTRY_SEED, TEAM_ADDRESS},
+        LogLevel, MediatorRegistry,
+    },
+    utils::{AccountInfoHelpers, OptionExt, ResultExt},
+};
+
+/// Suspends a registered mediator, or reinstates them when `suspended` is false. Suspended
+/// mediators keep their metadata but cannot mediate.
+pub fn suspend_mediator(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: LogLevel,
+    mediator: Pubkey,
+    suspended: bool,
+) -> ProgramResult {
+    log!(log_level, 4, "suspend_mediator called");
+    let account_info_iter = &mut accounts.iter();
+    let team_account_info = next_account_info(account_info_iter)?;
+    let registry_account_info = next_account_info(account_info_iter)?;
+
+    team_account_info
+        .assert_signer()
+        .error_log("Error @ team_account_info.assert_signer")?;
+    team_account_info
+        .assert_key_match(&TEAM_ADDRESS)
+        .error_log("Only the team can manage mediators")?;
+    registry_account_info
+        .assert_seed(program_id, &[MEDIATOR_REGISTRY_SEED])
+        .error_log("Error @ registry_account_info.assert_seed")?;
+
+    let mut registry_data = MediatorRegistry::parse(registry_account_info, program_id)?;
+    registry_data
+        .mediators
+        .iter_mut()
+        .find(|registered| registered.key == mediator)
+        .error_log("Error @ mediator is not registered")?
+        .suspended = suspended;
+
+    registry_data
+        .serialize(&mut &mut registry_account_info.data.borrow_mut()[..])
+        .error_log("Error @ registry_data.serialize")?;
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    vote,
+};
+
+use crate::{
+    error::InglError,
+    log,
+    state::{
+        consts::{PDA_AUTHORIZED_WITHDRAWER_SEED, PROGRAM_STORAGE_SEED},
+        LogLevel, Storage, StoredListingType,
+    },
+    utils::{AccountInfoHelpers, ResultExt},
+};
+
+/// Lets the seller keep updating the commission of a listed validator. The vote program requires
+/// the authorized withdrawer to sign, so the program signs on the seller's behalf.
+pub fn update_commission(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    commission: u8,
+    log_level: LogLevel,
+) -> ProgramResult {
+    log!(log_level, 4, "update_commission called");
+    let account_info_iter = &mut accounts.iter();
+    let authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    let storage_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let pda_authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    // Bundles also take their other vote accounts, in the order they are recorded.
+    let bundled_vote_account_infos = account_info_iter.as_slice();
+
+    authorized_withdrawer_info
+        .assert_signer()
+        .error_log("Error @ authorized_withdrawer_info.assert_signer")?;
+    storage_account_info
+        .assert_seed(
+            program_id,
+            &[PROGRAM_STORAGE_SEED, vote_account_info.key.as_ref()],
+        )
+        .error_log("Error @ storage_account_info.assert_seed")?;
+    let (_pda_authorized_withdrawer_key, pda_aw_bump) = pda_authorized_withdrawer_info
+        .assert_seed(program_id, &[PDA_AUTHORIZED_WITHDRAWER_SEED])
+        .error_log("Error @ pda_authorized_withdrawer_info.assert_seed")?;
+
+    let storage_data = Storage::parse(storage_account_info, program_id)?;
+    authorized_withdrawer_info
+        .assert_key_match(&storage_data.authorized_withdrawer)
+        .error_log("Only the seller can update the commission")?;
+
+    if let Some(_purchase) = storage_data.purchase {
+        Err(InglError::TooLate.utilize("validator is already bought"))?
+    }
+    if let StoredListingType::EnglishAuction(auction) = &storage_data.listing_type {
+        if auction.highest_bid.is_some() {
+            Err(InglError::TooLate.utilize("commission is locked while an auction has a bid"))?
+        }
+    }
+    if let StoredListingType::Lease(lease) = &storage_data.listing_type {
+        if let Some(_lessee) = lease.lessee {
+            Err(InglError::TooLate.utilize("commission is locked while the validator is leased"))?
+        }
+    }
+    if let Some(revenue_share) = &storage_data.revenue_share {
+        if !revenue_share.holders.is_empty() {
+            Err(InglError::TooLate
+                .utilize("commission is locked once revenue shares have been sold"))?
+        }
+    }
+    if commission > 100 {
+        Err(InglError::BeyondBounds.utilize("commission can't exceed 100"))?
+    }
+    if bundled_vote_account_infos.len() < storage_data.bundled_vote_accounts.len() {
+        Err(InglError::InvalidData.utilize("bundled vote accounts are missing"))?
+    }
+
+    for (vote_account, expected_vote_account) in std::iter::once(vote_account_info)
+        .chain(bundled_vote_account_infos)
+        .zip(std::iter::once(&storage_data.vote_account).chain(&storage_data.bundled_vote_accounts))
+    {
+        vote_account
+            .assert_key_match(expected_vote_account)
+            .error_log("Error @ vote_account.assert_key_match")?;
+        vote_account
+            .assert_owner(&vote::program::id())
+            .error_log("Error @ vote_account.assert_owner")?;
+
+        log!(log_level, 2, "update_commission: updating commission");
+        invoke_signed(
+            &vote::instruction::update_commission(
+                vote_account.key,
+                pda_authorized_withdrawer_info.key,
+                commission,
+            ),
+            &[vote_account.clone(), pda_authorized_withdrawer_info.clone()],
+            &[&[PDA_AUTHORIZED_WITHDRAWER_SEED, &[pda_aw_bump]]],
+        )
+        .error_log("Error @ vote::instruction::update_commission")?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,167 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+
+use crate::{
+    error::InglError,
+    instruction::SecondaryItem,
+    log,
+    processes::list::{verify_listing_details, verify_secondary_items},
+    state::{consts::PROGRAM_STORAGE_SEED, LogLevel, Storage, StoredListingType},
+    utils::{move_lamports, AccountInfoHelpers, ResultExt},
+};
+
+/// Edits an unsold listing in place, including its buyer allowlist. Fields left as `None` are
+/// unchanged. The storage account is resized to fit, with the seller topping up or receiving back
+/// the rent difference.
+pub fn update_listing(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    authorized_withdrawer_cost: Option<u64>,
+    mediation_interval: Option<u32>,
+    secondary_items: Option<Vec<SecondaryItem>>,
+    description: Option<String>,
+    validator_name: Option<String>,
+    validator_logo_url: Option<String>,
+    allowed_buyers: Option<Vec<Pubkey>>,
+    log_level: LogLevel,
+) -> ProgramResult {
+    log!(log_level, 4, "update_listing called");
+    let account_info_iter = &mut accounts.iter();
+    let authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    let storage_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let _system_program_info = next_account_info(account_info_iter)?;
+
+    authorized_withdrawer_info
+        .assert_signer()
+        .error_log("Error @ authorized_withdrawer_info.assert_signer")?;
+    storage_account_info
+        .assert_seed(
+            program_id,
+            &[PROGRAM_STORAGE_SEED, vote_account_info.key.as_ref()],
+        )
+        .error_log("Error @ storage_account_info.assert_seed")?;
+
+    let mut storage_data = Storage::parse(storage_account_info, program_id)?;
+    authorized_withdrawer_info
+        .assert_key_match(&storage_data.authorized_withdrawer)
+        .error_log("Only the seller can update a listing")?;
+
+    if let Some(_purchase) = storage_data.purchase {
+        Err(InglError::TooLate.utilize("a sold listing can't be updated"))?
+    }
+    if let StoredListingType::EnglishAuction(auction) = &storage_data.listing_type {
+        if auction.highest_bid.is_some() {
+            Err(InglError::TooLate.utilize("an auction with a standing bid can't be updated"))?
+        }
+    }
+    if let StoredListingType::Lease(lease) = &storage_data.listing_type {
+        if let Some(_lessee) = lease.lessee {
+            Err(InglError::TooLate.utilize("a leased listing can't be updated"))?
+        }
+    }
+
+    if let Some(authorized_withdrawer_cost) = authorized_withdrawer_cost {
+        if let StoredListingType::DutchAuction(auction) = &storage_data.listing_type {
+            if auction.floor_price > authorized_withdrawer_cost {
+                Err(InglError::InvalidData
+                    .utilize("Floor price can't be higher than the starting price"))?
+            }
+        }
+        storage_data.authorized_withdrawer_cost = authorized_withdrawer_cost;
+    }
+    if let Some(mediation_interval) = mediation_interval {
+        storage_data.mediation_interval = mediation_interval;
+    }
+    if let Some(secondary_items) = secondary_items {
+        storage_data.secondary_items = secondary_items
+            .iter()
+            .map(|item| item.to_stored())
+            .collect();
+    }
+    if let Some(description) = description {
+        storage_data.description = description;
+    }
+    if let Some(validator_name) = validator_name {
+        if validator_name != storage_data.validator_name {
+            storage_data.validator_info = None;
+        }
+        storage_data.validator_name = validator_name;
+    }
+    if let Some(validator_logo_url) = validator_logo_url {
+        storage_data.validator_logo_url = validator_logo_url;
+    }
+    if let Some(allowed_buyers) = allowed_buyers {
+        storage_data.allowed_buyers = allowed_buyers;
+    }
+
+    if (storage_data.revenue_share.is_some()
+        || matches!(storage_data.listing_type, StoredListingType::Lease(_)))
+        && !storage_data.secondary_items.is_empty()
+    {
+        Err(InglError::InvalidData
+            .utilize("Revenue share and lease listings can't include secondary items"))?
+    }
+
+    verify_listing_details(
+        storage_data.mediation_interval,
+        &storage_data.validator_name,
+    )?;
+    if storage_data.mediation_interval >= storage_data.silence_interval {
+        Err(InglError::InvalidData
+            .utilize("Mediation interval must end before the silence interval"))?
+    }
+    verify_secondary_items(
+        &storage_data.secondary_items,
+        !storage_data.bundled_vote_accounts.is_empty(),
+        !storage_data.self_stake.is_empty(),
+    )?;
+
+    let space = storage_data.get_space();
+    let required_lamports = Rent::get()?.minimum_balance(space);
+    let current_lamports = storage_account_info.lamports();
+    if required_lamports > current_lamports {
+        log!(log_level, 2, "update_listing: topping up storage rent");
+        invoke(
+            &system_instruction::transfer(
+                authorized_withdrawer_info.key,
+                storage_account_info.key,
+                required_lamports - current_lamports,
+            ),
+            &[
+                authorized_withdrawer_info.clone(),
+                storage_account_info.clone(),
+            ],
+        )
+        .error_log("Error @ storage rent top up")?;
+    } else if current_lamports > required_lamports {
+        log!(
+            log_level,
+            2,
+            "update_listing: refunding excess storage rent"
+        );
+        move_lamports(
+            storage_account_info,
+            authorized_withdrawer_info,
+            current_lamports - required_lamports,
+        )
+        .error_log("Error @ storage rent refund")?;
+    }
+
+    storage_account_info
+        .realloc(space, false)
+        .error_log("Error @ storage_account_info.realloc")?;
+    storage_data
+        .serialize(&mut &mut storage_account_info.data.borrow_mut()[..])
+        .error_log("Error @ storage_data.serialize")?;
+
+    Ok(())
+}
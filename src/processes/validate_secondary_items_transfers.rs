@@ -1,17 +1,17 @@
 use borsh::BorshSerialize;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    clock::Clock,
     entrypoint::ProgramResult,
-    program::invoke_signed,
     pubkey::Pubkey,
-    system_instruction,
 };
 
 use crate::{
     error::InglError,
+    processes::claim::record_claims,
     state::{
-        consts::{ESCROW_ACCOUNT_SEED, PROGRAM_STORAGE_SEED},
-        LogLevel, Storage,
+        consts::{PROGRAM_STORAGE_SEED, TEAM_ADDRESS},
+        Claim, LogLevel, SecondaryItemKind, Storage,
     },
     utils::{get_clock_data, AccountInfoHelpers, OptionExt, ResultExt},
 };
@@ -26,19 +26,22 @@ pub fn validate_secondary_items_transfers(
     let account_info_iter = &mut accounts.iter();
     let buyer_account_info = next_account_info(account_info_iter)?;
     let storage_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
     let escrow_account_info = next_account_info(account_info_iter)?;
     let authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    let item_claims_account_info = next_account_info(account_info_iter)?;
+    let claims_account_info = next_account_info(account_info_iter)?;
 
     let clock_data = get_clock_data(account_info_iter, clock_is_from_account)?;
 
     buyer_account_info.assert_signer()?;
 
     storage_account_info
-        .assert_seed(program_id, &[PROGRAM_STORAGE_SEED])
+        .assert_seed(
+            program_id,
+            &[PROGRAM_STORAGE_SEED, vote_account_info.key.as_ref()],
+        )
         .error_log("Error @ storage account pda assertion")?;
-    let (_escrow_accout_key, escrow_pumb) = escrow_account_info
-        .assert_seed(program_id, &[ESCROW_ACCOUNT_SEED])
-        .error_log("Error @ escrow account pda assertion")?;
 
     let mut storage_data = Storage::parse(storage_account_info, program_id)?;
     authorized_withdrawer_info
@@ -61,6 +64,9 @@ pub fn validate_secondary_items_transfers(
         )
         .error_log("Only the buyer can validate secondary items transfers")?;
 
+    if storage_data.secondary_items[item_index as usize].kind != SecondaryItemKind::Custom {
+        Err(InglError::InvalidData.utilize("Built-in secondary items are validated by the program"))?
+    }
     if let Some(_date_validated) = storage_data.secondary_items[item_index as usize].date_validated
     {
         Err(InglError::TooLate.utilize("Secondary item has already been validated"))?
@@ -69,47 +75,131 @@ pub fn validate_secondary_items_transfers(
     storage_data.secondary_items[item_index as usize].date_validated =
         Some(clock_data.unix_timestamp as u32);
 
+    settle_secondary_item(
+        program_id,
+        &mut storage_data,
+        item_index,
+        vote_account_info,
+        escrow_account_info,
+        item_claims_account_info,
+        buyer_account_info,
+    )?;
+    release_escrow_once_validated(
+        program_id,
+        &mut storage_data,
+        vote_account_info,
+        escrow_account_info,
+        claims_account_info,
+        buyer_account_info,
+        &clock_data,
+    )?;
+
+    storage_data
+        .serialize(&mut &mut storage_account_info.data.borrow_mut()[..])
+        .error_log("Error @ storage_data.serialize")?;
+    Ok(())
+}
+
+/// Records claims on the escrow for the cost and collateral of the secondary item at
+/// `item_index` as soon as it is validated or mediated, in a claims account of its own whose rent
+/// the payer covers. The other items keep settling independently.
+pub fn settle_secondary_item<'a>(
+    program_id: &Pubkey,
+    storage_data: &mut Storage,
+    item_index: u32,
+    vote_account_info: &AccountInfo<'a>,
+    escrow_account_info: &AccountInfo<'a>,
+    item_claims_account_info: &AccountInfo<'a>,
+    payer_account_info: &AccountInfo<'a>,
+) -> ProgramResult {
+    let purchase = storage_data
+        .purchase
+        .error_log("purchase must have taken place")?;
+    let (to_buyer, to_seller, to_team) = storage_data
+        .secondary_items
+        .get(item_index as usize)
+        .error_log("Error @ secondary item index out of bounds")?
+        .settlement()?;
+    let to_buyer = to_buyer.min(purchase.escrowed);
+    let to_seller = to_seller.min(purchase.escrowed - to_buyer);
+    let to_team = to_team.min(purchase.escrowed - to_buyer - to_seller);
+    record_claims(
+        program_id,
+        &purchase,
+        Some(item_index),
+        storage_data.payment_mint,
+        vote_account_info,
+        escrow_account_info,
+        item_claims_account_info,
+        payer_account_info,
+        &[
+            Claim {
+                claimant: purchase.buyer,
+                amount: to_buyer,
+            },
+            Claim {
+                claimant: storage_data.authorized_withdrawer,
+                amount: to_seller,
+            },
+            Claim {
+                claimant: TEAM_ADDRESS,
+                amount: to_team,
+            },
+        ],
+    )
+    .error_log("Error @ record_claims")?;
+
+    storage_data
+        .purchase
+        .as_mut()
+        .error_log("purchase must have taken place")?
+        .escrowed -= to_buyer + to_seller + to_team;
+    Ok(())
+}
+
+/// Finalizes the purchase once every secondary item is settled, recording a claim for the seller
+/// on what is left in escrow. The payer covers the rent of the claims account.
+pub fn release_escrow_once_validated<'a>(
+    program_id: &Pubkey,
+    storage_data: &mut Storage,
+    vote_account_info: &AccountInfo<'a>,
+    escrow_account_info: &AccountInfo<'a>,
+    claims_account_info: &AccountInfo<'a>,
+    payer_account_info: &AccountInfo<'a>,
+    clock_data: &Clock,
+) -> ProgramResult {
     let invalidated_secondary_items = storage_data
         .secondary_items
         .iter()
         .filter(|item| item.date_validated.is_none())
         .count();
-    if invalidated_secondary_items == 0 {
-        let secondary_items_cost = storage_data
-            .secondary_items
-            .iter()
-            .map(|item| item.cost)
-            .sum::<u64>();
-
-        invoke_signed(
-            &system_instruction::transfer(
-                &escrow_account_info.key,
-                &buyer_account_info.key,
-                secondary_items_cost,
-            ),
-            &[escrow_account_info.clone(), buyer_account_info.clone()],
-            &[&[ESCROW_ACCOUNT_SEED, &[escrow_pumb]]],
-        )?;
-        invoke_signed(
-            &system_instruction::transfer(
-                &escrow_account_info.key,
-                &authorized_withdrawer_info.key,
-                escrow_account_info.lamports(),
-            ),
-            &[
-                escrow_account_info.clone(),
-                authorized_withdrawer_info.clone(),
-            ],
-            &[&[ESCROW_ACCOUNT_SEED, &[escrow_pumb]]],
-        )?;
-        storage_data
+    let purchase = storage_data
+        .purchase
+        .error_log("purchase must have taken place")?;
+    if invalidated_secondary_items == 0 && purchase.date_finalized.is_none() {
+        record_claims(
+            program_id,
+            &purchase,
+            None,
+            storage_data.payment_mint,
+            vote_account_info,
+            escrow_account_info,
+            claims_account_info,
+            payer_account_info,
+            &[Claim {
+                claimant: storage_data.authorized_withdrawer,
+                amount: purchase.escrowed,
+            }],
+        )
+        .error_log("Error @ record_claims")?;
+
+        let purchase = storage_data
             .purchase
-            .error_log("purchase must have taken place")?
-            .date_finalized = Some(clock_data.unix_timestamp as u32);
+            .as_mut()
+            .error_log("purchase must have taken place")?;
+        purchase.date_finalized = Some(clock_data.unix_timestamp as u32);
+        purchase.escrowed = 0;
     }
 
-    storage_data
-        .serialize(&mut &mut storage_account_info.data.borrow_mut()[..])
-        .error_log("Error @ storage_data.serialize")?;
     Ok(())
 }
@@ -0,0 +1,164 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+
+use crate::{
+    error::InglError,
+    log,
+    state::{
+        consts::{
+            MEDIATION_CASE_SEED, MEDIATION_CASE_VALIDATION_PHRASE, MEDIATOR_REGISTRY_SEED,
+            PROGRAM_STORAGE_SEED,
+        },
+        LogLevel, MediationCase, MediationShares, MediationVote, MediatorRegistry, Purchase,
+        Storage,
+    },
+    utils::{
+        create_pda_account, get_clock_data_from_account, AccountInfoHelpers, OptionExt, ResultExt,
+    },
+};
+
+/// Records the shares a registered mediator proposes for a dispute of a listing mediated by a
+/// panel. The first vote opens the mediation case, whose rent each voter tops up.
+pub fn vote_mediation(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: LogLevel,
+    mediation_shares: MediationShares,
+    item_index: Option<u32>,
+) -> ProgramResult {
+    log!(log_level, 4, "vote_mediation called");
+    let account_info_iter = &mut accounts.iter();
+    let mediator_account_info = next_account_info(account_info_iter)?;
+    let storage_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let mediation_case_info = next_account_info(account_info_iter)?;
+    let mediator_registry_info = next_account_info(account_info_iter)?;
+    let sysvar_clock_account_info = next_account_info(account_info_iter)?;
+
+    let clock_data =
+        get_clock_data_from_account(sysvar_clock_account_info).error_log("Error @ clock parse")?;
+
+    mediator_account_info
+        .assert_signer()
+        .error_log("Error @ mediator_account_info.assert_signer")?;
+    mediator_registry_info
+        .assert_seed(program_id, &[MEDIATOR_REGISTRY_SEED])
+        .error_log("Error @ mediator_registry_info.assert_seed")?;
+    let mediator_registry = MediatorRegistry::parse(mediator_registry_info, program_id)?;
+    if !mediator_registry.is_active_mediator(mediator_account_info.key) {
+        Err(InglError::NotAuthorized.utilize("only registered mediators can mediate"))?
+    }
+    storage_account_info
+        .assert_seed(
+            program_id,
+            &[PROGRAM_STORAGE_SEED, vote_account_info.key.as_ref()],
+        )
+        .error_log("Error @ storage_account_info.assert_seed")?;
+
+    let storage_data = Storage::parse(storage_account_info, program_id)?;
+    let mediation_panel = storage_data
+        .mediation_panel
+        .error_log("Error @ listing is not mediated by a panel")?;
+    let purchase = storage_data
+        .purchase
+        .error_log("mediation can only take place if purchase took place")?;
+    if let Some(_date_finalized) = purchase.date_finalized {
+        Err(InglError::TooLate.utilize("Purchase has already been finalized"))?
+    }
+    storage_data.assert_mediation_pending(item_index)?;
+    mediation_shares.verify_sum()?;
+
+    let escrow_seed = Purchase::escrow_seed(purchase.escrow_nonce);
+    let item_seed = Purchase::item_seed(item_index);
+    let (_mediation_case_key, mediation_case_bump) = mediation_case_info
+        .assert_seed(
+            program_id,
+            &[
+                MEDIATION_CASE_SEED,
+                vote_account_info.key.as_ref(),
+                &escrow_seed,
+                &item_seed,
+            ],
+        )
+        .error_log("Error @ mediation_case_info.assert_seed")?;
+
+    let vote = MediationVote {
+        mediator: *mediator_account_info.key,
+        mediation_shares,
+        date: clock_data.unix_timestamp as u32,
+    };
+    log!(log_level, 3, "vote_mediation: {:?}", vote);
+
+    if mediation_case_info.data_is_empty() {
+        log!(log_level, 2, "vote_mediation: opening mediation case");
+        let mediation_case = MediationCase {
+            validation_phrase: MEDIATION_CASE_VALIDATION_PHRASE,
+            vote_account: *vote_account_info.key,
+            purchase_date: purchase.date,
+            item_index,
+            deadline: (clock_data.unix_timestamp as u32)
+                .saturating_add(mediation_panel.voting_interval),
+            date_settled: None,
+            votes: vec![vote],
+        };
+        create_pda_account(
+            program_id,
+            mediator_account_info,
+            mediation_case_info,
+            mediation_case.get_space(),
+            &[
+                MEDIATION_CASE_SEED,
+                vote_account_info.key.as_ref(),
+                &escrow_seed,
+                &item_seed,
+                &[mediation_case_bump],
+            ],
+        )
+        .error_log("Error @ create_pda_account")?;
+        mediation_case
+            .serialize(&mut &mut mediation_case_info.data.borrow_mut()[..])
+            .error_log("Error @ mediation_case.serialize")?;
+        return Ok(());
+    }
+
+    let mut mediation_case = MediationCase::parse(mediation_case_info, program_id)?;
+    if mediation_case
+        .votes
+        .iter()
+        .any(|vote| vote.mediator == *mediator_account_info.key)
+    {
+        Err(InglError::TooLate.utilize("mediator has already voted"))?
+    }
+    mediation_case.votes.push(vote);
+
+    let space = mediation_case.get_space();
+    let required_lamports = Rent::get()?.minimum_balance(space);
+    let current_lamports = mediation_case_info.lamports();
+    if required_lamports > current_lamports {
+        invoke(
+            &system_instruction::transfer(
+                mediator_account_info.key,
+                mediation_case_info.key,
+                required_lamports - current_lamports,
+            ),
+            &[mediator_account_info.clone(), mediation_case_info.clone()],
+        )
+        .error_log("Error @ mediation case rent top up")?;
+    }
+    mediation_case_info
+        .realloc(space, false)
+        .error_log("Error @ mediation_case_info.realloc")?;
+    mediation_case
+        .serialize(&mut &mut mediation_case_info.data.borrow_mut()[..])
+        .error_log("Error @ mediation_case.serialize")?;
+
+    Ok(())
+}
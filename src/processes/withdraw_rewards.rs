@@ -1,8 +1,10 @@
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    clock::Clock,
     entrypoint::ProgramResult,
     program::invoke_signed,
     pubkey::Pubkey,
+    sysvar::Sysvar,
     vote,
 };
 
@@ -14,9 +16,11 @@ use crate::{
     utils::{AccountInfoHelpers, OptionExt, ResultExt},
 };
 
-pub fn withdraw_rewards(
+/// Withdraws the vote rewards of a listed vote account, or of every vote account of a bundle,
+/// to `Storage::rewards_recipient`, paying revenue share holders their recorded shares first.
+pub fn withdraw_rewards<'a>(
     program_id: &Pubkey,
-    accounts: &[AccountInfo],
+    accounts: &[AccountInfo<'a>],
     _log_level: LogLevel,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
@@ -29,7 +33,10 @@ pub fn withdraw_rewards(
         .assert_seed(program_id, &[PDA_AUTHORIZED_WITHDRAWER_SEED])
         .error_log("Error @ pda_authorized_withdrawer_info.assert_seed")?;
     storage_account_info
-        .assert_seed(program_id, &[PROGRAM_STORAGE_SEED])
+        .assert_seed(
+            program_id,
+            &[PROGRAM_STORAGE_SEED, vote_account_info.key.as_ref()],
+        )
         .error_log("Error @ storage_account_info.assert_seed")?;
 
     let storage_data = Storage::parse(storage_account_info, program_id)?;
@@ -42,30 +49,101 @@ pub fn withdraw_rewards(
         .error_log("Error @ vote_account_info.assert_key_match(&storage_data.vote_account)")?;
 
     authorized_withdrawer_info
-        .assert_key_match(&storage_data.authorized_withdrawer)
-        .error_log(
-            "Error @ authorized_withdrawer_info.assert_key_match(&storage_data.authorized_withdrawer)",
-        )?;
+        .assert_key_match(&storage_data.rewards_recipient(Clock::get()?.epoch))
+        .error_log("Error @ authorized_withdrawer_info.assert_key_match(&rewards_recipient)")?;
 
-    let lamports = vote_account_info
-        .lamports()
-        .checked_sub(VoteState::min_lamports())
-        .error_log("Error @ vote_account_info.lamports().checked_sub(VoteState::min_lamports())")?;
+    withdraw_vote_rewards(
+        &storage_data,
+        vote_account_info,
+        authorized_withdrawer_info,
+        pda_authorized_withdrawer_info,
+        pda_authority_bump,
+        account_info_iter,
+    )
+}
+
+/// Withdraws the rewards of `vote_account_info`, and of the other vote accounts of a bundle, to
+/// `recipient_info`, paying revenue share holders their recorded shares first. The holders and the
+/// bundled vote accounts are taken from `account_info_iter`, in the order they are recorded.
+pub fn withdraw_vote_rewards<'a, 'b>(
+    storage_data: &Storage,
+    vote_account_info: &'b AccountInfo<'a>,
+    recipient_info: &'b AccountInfo<'a>,
+    pda_authorized_withdrawer_info: &'b AccountInfo<'a>,
+    pda_authority_bump: u8,
+    account_info_iter: &mut std::slice::Iter<'b, AccountInfo<'a>>,
+) -> ProgramResult {
+    let pda_seeds: &[&[u8]] = &[PDA_AUTHORIZED_WITHDRAWER_SEED, &[pda_authority_bump]];
+    let withdraw_to = |vote_account: &AccountInfo<'a>,
+                       recipient: &AccountInfo<'a>,
+                       lamports: u64|
+     -> ProgramResult {
+        invoke_signed(
+            &vote::instruction::withdraw(
+                vote_account.key,
+                pda_authorized_withdrawer_info.key,
+                lamports,
+                recipient.key,
+            ),
+            &[
+                vote_account.clone(),
+                recipient.clone(),
+                pda_authorized_withdrawer_info.clone(),
+            ],
+            &[pda_seeds],
+        )
+    };
+
+    // Revenue share holders come first, in the order they are recorded, followed by the other
+    // vote accounts of a bundle.
+    let mut holder_account_infos = Vec::new();
+    if let Some(revenue_share) = &storage_data.revenue_share {
+        for holder in &revenue_share.holders {
+            let holder_account_info = next_account_info(account_info_iter)?;
+            holder_account_info
+                .assert_key_match(&holder.holder)
+                .error_log("Error @ holder_account_info.assert_key_match")?;
+            holder_account_infos.push(holder_account_info);
+        }
+    }
+    let mut vote_account_infos = vec![vote_account_info];
+    for bundled_vote_account in &storage_data.bundled_vote_accounts {
+        let bundled_vote_account_info = next_account_info(account_info_iter)?;
+        bundled_vote_account_info
+            .assert_key_match(bundled_vote_account)
+            .error_log("Error @ bundled_vote_account_info.assert_key_match")?;
+        bundled_vote_account_info
+            .assert_owner(&vote::program::id())
+            .error_log("Error @ bundled_vote_account_info.assert_owner")?;
+        vote_account_infos.push(bundled_vote_account_info);
+    }
+
+    for vote_account_info in vote_account_infos {
+        let lamports = vote_account_info
+            .lamports()
+            .checked_sub(VoteState::min_lamports())
+            .error_log(
+                "Error @ vote_account_info.lamports().checked_sub(VoteState::min_lamports())",
+            )?;
+
+        let mut seller_lamports = lamports;
+        if let Some(revenue_share) = &storage_data.revenue_share {
+            for (holder_account_info, holder_lamports) in holder_account_infos
+                .iter()
+                .zip(revenue_share.split(lamports))
+            {
+                if holder_lamports > 0 {
+                    withdraw_to(vote_account_info, holder_account_info, holder_lamports)
+                        .error_log("Error @ revenue share withdrawal")?;
+                }
+                seller_lamports -= holder_lamports;
+            }
+        }
 
-    invoke_signed(
-        &vote::instruction::withdraw(
-            vote_account_info.key,
-            pda_authorized_withdrawer_info.key,
-            lamports,
-            authorized_withdrawer_info.key,
-        ),
-        &[
-            vote_account_info.clone(),
-            authorized_withdrawer_info.clone(),
-            pda_authorized_withdrawer_info.clone(),
-        ],
-        &[&[PDA_AUTHORIZED_WITHDRAWER_SEED, &[pda_authority_bump]]],
-    )?;
+        if seller_lamports > 0 {
+            withdraw_to(vote_account_info, recipient_info, seller_lamports)?;
+        }
+    }
 
     Ok(())
 }
@@ -3,10 +3,19 @@ use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubke
 use crate::{
     instruction::InstructionEnum,
     processes::{
-        buy::buy_validator, delist::delist_validator, list::list_validator, mediate::mediate,
-        request_mediation::request_mediation,
+        accept_offer::accept_offer, add_mediator::add_mediator, buy::buy_validator,
+        buy_revenue_share::buy_revenue_share, cancel_offer::cancel_offer, claim::claim,
+        delist::delist_validator, delist_expired::delist_expired, end_lease::end_lease,
+        exercise_buyback::exercise_buyback, expire_buyback::expire_buyback, finalize::finalize,
+        forfeit_installments::forfeit_installments, list::list_validator, make_offer::make_offer,
+        mediate::mediate, pay_installment::pay_installment, place_bid::place_bid,
+        refund_undelivered_items::refund_undelivered_items, remove_mediator::remove_mediator,
+        request_mediation::request_mediation, rotate_validator_identity::rotate_validator_identity,
+        settle_auction::settle_auction, settle_mediation::settle_mediation,
+        start_lease::start_lease, suspend_mediator::suspend_mediator,
+        update_commission::update_commission, update_listing::update_listing,
         validate_secondary_items_transfers::validate_secondary_items_transfers,
-        withdraw_rewards::withdraw_rewards,
+        vote_mediation::vote_mediation, withdraw_rewards::withdraw_rewards,
     },
 };
 
@@ -21,41 +30,179 @@ pub fn process_instruction(
         InstructionEnum::List {
             log_level,
             authorized_withdrawer_cost,
+            listing_type,
             mediatable_date,
             secondary_items,
             description,
             validator_name,
             validator_logo_url,
+            validator_info,
+            expires_at,
+            allowed_buyers,
+            payment_mint,
+            installment_terms,
+            revenue_share_basis_points,
+            buyback_clause,
+            self_stake_accounts,
+            delivery_interval,
+            silence_interval,
+            mediation_panel,
         } => list_validator(
             program_id,
             accounts,
             authorized_withdrawer_cost,
+            listing_type,
             secondary_items,
             description,
             log_level,
             mediatable_date,
             validator_name,
             validator_logo_url,
+            validator_info,
+            expires_at,
+            allowed_buyers,
+            payment_mint,
+            installment_terms,
+            revenue_share_basis_points,
+            buyback_clause,
+            self_stake_accounts,
+            delivery_interval,
+            silence_interval,
+            mediation_panel,
             false,
         )?,
         InstructionEnum::Delist { log_level } => delist_validator(program_id, accounts, log_level)?,
-        InstructionEnum::Buy { log_level } => buy_validator(program_id, accounts, log_level)?,
+        InstructionEnum::DelistExpired { log_level } => {
+            delist_expired(program_id, accounts, log_level)?
+        }
+        InstructionEnum::Buy {
+            log_level,
+            authorized_voter,
+            max_price,
+        } => buy_validator(program_id, accounts, authorized_voter, max_price, log_level)?,
         InstructionEnum::WithdrawRewards { log_level } => {
             withdraw_rewards(program_id, accounts, log_level)?
         }
-        InstructionEnum::RequestMediation { log_level } => {
-            request_mediation(program_id, accounts, log_level, false)?
-        }
+        InstructionEnum::RequestMediation {
+            log_level,
+            item_index,
+        } => request_mediation(program_id, accounts, log_level, item_index, false)?,
         InstructionEnum::Mediate {
             log_level,
             mediation_shares,
-        } => mediate(program_id, accounts, mediation_shares, log_level, false)?,
+            item_index,
+        } => mediate(
+            program_id,
+            accounts,
+            mediation_shares,
+            item_index,
+            log_level,
+            false,
+        )?,
         InstructionEnum::ValidateSecondaryItemsTransfers {
             item_index,
             log_level,
         } => {
             validate_secondary_items_transfers(program_id, accounts, log_level, item_index, false)?
         }
+        InstructionEnum::PlaceBid { log_level, amount } => {
+            place_bid(program_id, accounts, amount, log_level, false)?
+        }
+        InstructionEnum::SettleAuction { log_level } => {
+            settle_auction(program_id, accounts, log_level)?
+        }
+        InstructionEnum::MakeOffer {
+            log_level,
+            amount,
+            expiry_date,
+        } => make_offer(program_id, accounts, amount, expiry_date, log_level, false)?,
+        InstructionEnum::CancelOffer { log_level } => {
+            cancel_offer(program_id, accounts, log_level)?
+        }
+        InstructionEnum::AcceptOffer { log_level } => {
+            accept_offer(program_id, accounts, log_level)?
+        }
+        InstructionEnum::UpdateListing {
+            log_level,
+            authorized_withdrawer_cost,
+            mediation_interval,
+            secondary_items,
+            description,
+            validator_name,
+            validator_logo_url,
+            allowed_buyers,
+        } => update_listing(
+            program_id,
+            accounts,
+            authorized_withdrawer_cost,
+            mediation_interval,
+            secondary_items,
+            description,
+            validator_name,
+            validator_logo_url,
+            allowed_buyers,
+            log_level,
+        )?,
+        InstructionEnum::PayInstallment { log_level } => {
+            pay_installment(program_id, accounts, log_level)?
+        }
+        InstructionEnum::ForfeitInstallments { log_level } => {
+            forfeit_installments(program_id, accounts, log_level)?
+        }
+        InstructionEnum::BuyRevenueShare {
+            log_level,
+            basis_points,
+        } => buy_revenue_share(program_id, accounts, basis_points, log_level)?,
+        InstructionEnum::StartLease { log_level } => start_lease(program_id, accounts, log_level)?,
+        InstructionEnum::EndLease { log_level } => end_lease(program_id, accounts, log_level)?,
+        InstructionEnum::ExerciseBuyback { log_level } => {
+            exercise_buyback(program_id, accounts, log_level)?
+        }
+        InstructionEnum::ExpireBuyback { log_level } => {
+            expire_buyback(program_id, accounts, log_level)?
+        }
+        InstructionEnum::UpdateCommission {
+            log_level,
+            commission,
+        } => update_commission(program_id, accounts, commission, log_level)?,
+        InstructionEnum::RotateValidatorIdentity { log_level } => {
+            rotate_validator_identity(program_id, accounts, log_level)?
+        }
+        InstructionEnum::Claim { log_level } => claim(program_id, accounts, log_level)?,
+        InstructionEnum::RefundUndeliveredItems { log_level } => {
+            refund_undelivered_items(program_id, accounts, log_level)?
+        }
+        InstructionEnum::Finalize { log_level } => finalize(program_id, accounts, log_level)?,
+        InstructionEnum::AddMediator {
+            log_level,
+            mediator,
+            name,
+            contact,
+        } => add_mediator(program_id, accounts, log_level, mediator, name, contact)?,
+        InstructionEnum::RemoveMediator {
+            log_level,
+            mediator,
+        } => remove_mediator(program_id, accounts, log_level, mediator)?,
+        InstructionEnum::SuspendMediator {
+            log_level,
+            mediator,
+            suspended,
+        } => suspend_mediator(program_id, accounts, log_level, mediator, suspended)?,
+        InstructionEnum::VoteMediation {
+            log_level,
+            mediation_shares,
+            item_index,
+        } => vote_mediation(
+            program_id,
+            accounts,
+            log_level,
+            mediation_shares,
+            item_index,
+        )?,
+        InstructionEnum::SettleMediation {
+            log_level,
+            item_index,
+        } => settle_mediation(program_id, accounts, log_level, item_index)?,
     }
 
     Ok(())
@@ -2,13 +2,14 @@
 use crate::{
     colored_log,
     error::InglError,
-    utils::{AccountInfoHelpers, ResultExt},
+    utils::{AccountInfoHelpers, OptionExt, ResultExt},
 };
 use borsh::{BorshDeserialize, BorshSerialize};
 use ingl_macros::Validate;
 use solana_program::{
-    account_info::AccountInfo, borsh::try_from_slice_unchecked, program_error::ProgramError,
-    pubkey::Pubkey, rent::Rent, slot_history::Slot, stake_history::Epoch, sysvar::Sysvar,
+    account_info::AccountInfo, borsh::try_from_slice_unchecked, entrypoint::ProgramResult,
+    program_error::ProgramError, pubkey::Pubkey, rent::Rent, slot_history::Slot,
+    stake_history::Epoch, sysvar::Sysvar,
 };
 use std::collections::{BTreeMap, VecDeque};
 
@@ -20,17 +21,34 @@ pub mod consts {
 
     pub const PDA_AUTHORIZED_WITHDRAWER_SEED: &[u8] = b"authorized_withdrawer";
     pub const PROGRAM_STORAGE_SEED: &[u8] = b"program_storage";
-    pub const PDA_UPGRADE_AUTHORITY_SEED: &[u8] = b"upgrade_authority";
     pub const ESCROW_ACCOUNT_SEED: &[u8] = b"escrow_account";
+    pub const BID_ESCROW_SEED: &[u8] = b"bid_escrow";
+    pub const OFFER_SEED: &[u8] = b"offer";
+    pub const BUYBACK_SEED: &[u8] = b"buyback";
+    pub const CLAIMS_SEED: &[u8] = b"claims";
     pub const REGISTRY_STORAGE_SEED: &[u8] = b"marketplace_storage";
+    pub const MEDIATOR_REGISTRY_SEED: &[u8] = b"mediator_registry";
+    pub const MEDIATION_CASE_SEED: &[u8] = b"mediation_case";
 
     pub const ESCROWED_BASIS_POINTS: u16 = 2000;
     pub const TEAM_FEES_BASIS_POINTS: u16 = 10;
+    pub const MAX_REVENUE_SHARE_HOLDERS: usize = 16;
+    pub const MAX_BUNDLED_VOTE_ACCOUNTS: usize = 8;
+    pub const MAX_SELF_STAKE_ACCOUNTS: usize = 8;
+    pub const DEFAULT_SILENCE_INTERVAL: u32 = 60 * 86400;
+    pub const MAX_MEDIATORS: usize = 32;
+    pub const MIN_MEDIATION_PANEL_THRESHOLD: u8 = 2;
 
     pub const STORAGE_VALIDATION_PHRASE: u32 = 838_927_652;
+    pub const OFFER_VALIDATION_PHRASE: u32 = 736_281_945;
+    pub const BUYBACK_VALIDATION_PHRASE: u32 = 519_374_826;
+    pub const CLAIMS_VALIDATION_PHRASE: u32 = 604_817_392;
+    pub const MEDIATOR_REGISTRY_VALIDATION_PHRASE: u32 = 927_164_358;
+    pub const MEDIATION_CASE_VALIDATION_PHRASE: u32 = 358_216_947;
+
+    pub const VALIDATOR_INFO_ID: Pubkey = pubkey!("Va1idator1nfo111111111111111111111111111111");
 
     pub const TEAM_ADDRESS: Pubkey = pubkey!("Et2tm6NsfBZJbEYXtWTv9k51V4tWtQvufexSgXoDRGVA");
-    pub const MEDIATORS: [Pubkey; 1] = [pubkey!("Et2tm6NsfBZJbEYXtWTv9k51V4tWtQvufexSgXoDRGVA")];
 
     pub mod program_registry {
 
@@ -47,8 +65,39 @@ pub struct Storage {
     pub validation_phrase: u32,
     pub authorized_withdrawer: Pubkey,
     pub vote_account: Pubkey,
+    /// Other vote accounts sold along with `vote_account` as a single package.
+    pub bundled_vote_accounts: Vec<Pubkey>,
+    /// Authorized voters of `vote_account`, then of each bundled vote account, when they were
+    /// listed. A voter rotated since is logged when the program overrides it on sale.
+    pub authorized_voters: Vec<Pubkey>,
     pub authorized_withdrawer_cost: u64,
+    /// Stake accounts delegated to the listed vote accounts. The program holds their staker and
+    /// withdrawer until they are handed over with the vote accounts. Their value is shown apart
+    /// from `authorized_withdrawer_cost`, which prices the whole sale.
+    pub self_stake: Vec<SelfStake>,
+    pub listing_type: StoredListingType,
+    /// SPL mint the listing is priced in, or `None` for lamports.
+    pub payment_mint: Option<Pubkey>,
     pub mediation_interval: u32,
+    /// Seconds after the purchase within which the secondary items must be delivered. Past it,
+    /// the buyer can get the undelivered items refunded without a mediator.
+    pub delivery_interval: Option<u32>,
+    /// Seconds after the purchase past which the seller can finalize it if the buyer neither
+    /// validated the secondary items nor requested mediation.
+    pub silence_interval: u32,
+    /// Set for high-value listings whose disputes are decided by several registered mediators
+    /// voting with `VoteMediation` rather than by a single `Mediate`.
+    pub mediation_panel: Option<MediationPanel>,
+    pub expires_at: Option<u32>,
+    /// Known to buyers ahead of the purchase, it scopes the escrow of the purchase of this
+    /// listing. See `Purchase::escrow_seed`.
+    pub date_listed: u32,
+    pub allowed_buyers: Vec<Pubkey>,
+    pub installment_plan: Option<InstallmentPlan>,
+    pub revenue_share: Option<RevenueShare>,
+    pub buyback_clause: Option<BuybackClause>,
+    /// Set when a buyer relists a validator bought with a right of first refusal.
+    pub first_refusal: Option<FirstRefusal>,
     pub purchase: Option<Purchase>,
     pub request_mediation_date: Option<u32>,
     pub mediation_date: Option<u32>,
@@ -57,14 +106,43 @@ pub struct Storage {
     pub description: String,
     pub validator_name: String,
     pub validator_logo_url: String,
+    /// Config account whose published name matched `validator_name` when it was last set.
+    pub validator_info: Option<Pubkey>,
 }
 
 impl Storage {
     pub fn get_space(&self) -> usize {
         4 + 32
             + 32
+            + 4
+            + 32 * self.bundled_vote_accounts.len()
+            + 4
+            + 32 * self.authorized_voters.len()
             + 8
             + 4
+            + SelfStake::get_space() * self.self_stake.len()
+            + StoredListingType::get_space()
+            + 33
+            + 4
+            + 5
+            + 5
+            + 4
+            + 1
+            + MediationPanel::get_space()
+            + 4
+            + 4
+            + 32 * self.allowed_buyers.len()
+            + 1
+            + InstallmentPlan::get_space()
+            + 1
+            + self
+                .revenue_share
+                .as_ref()
+                .map_or(0, |revenue_share| revenue_share.get_space())
+            + 1
+            + BuybackClause::get_space()
+            + 1
+            + FirstRefusal::get_space()
             + 1
             + Purchase::get_space()
             + 5
@@ -83,33 +161,677 @@ impl Storage {
             + 1
             + 4
             + self.validator_logo_url.len()
+            + 33
+    }
+
+    pub fn is_expired(&self, date: u32) -> bool {
+        match self.expires_at {
+            Some(expires_at) => date >= expires_at,
+            None => false,
+        }
+    }
+
+    /// Private listings can only be bought by the buyers on their allowlist.
+    pub fn assert_allowed_buyer(&self, buyer: &Pubkey) -> ProgramResult {
+        if !self.allowed_buyers.is_empty() && !self.allowed_buyers.contains(buyer) {
+            Err(InglError::BuyerNotAllowed.utilize(&buyer.to_string()))?
+        }
+        Ok(())
+    }
+
+    /// Account entitled to the vote rewards withdrawn while the program holds the withdrawer:
+    /// the lessee of a running lease, the buyer once the validator is paid for, or else the seller.
+    pub fn rewards_recipient(&self, epoch: u64) -> Pubkey {
+        if let StoredListingType::Lease(lease) = &self.listing_type {
+            if let (true, Some(lessee)) = (lease.is_active(epoch), lease.lessee) {
+                return lessee;
+            }
+        }
+        match (&self.purchase, &self.installment_plan) {
+            (Some(purchase), None) => purchase.buyer,
+            (Some(purchase), Some(plan)) if plan.is_paid_in_full() => purchase.buyer,
+            _ => self.authorized_withdrawer,
+        }
+    }
+
+    /// Only the holder of a right of first refusal can buy the listing during its response period.
+    pub fn assert_first_refusal(&self, buyer: &Pubkey, date: u32) -> ProgramResult {
+        if let Some(first_refusal) = &self.first_refusal {
+            if date < first_refusal.until && first_refusal.holder != *buyer {
+                Err(InglError::BuyerNotAllowed
+                    .utilize("listing is reserved for the right of first refusal holder"))?
+            }
+        }
+        Ok(())
+    }
+
+    /// Authorized voter recorded for `vote_account` when it was listed.
+    pub fn listed_authorized_voter(&self, vote_account: &Pubkey) -> Option<Pubkey> {
+        std::iter::once(&self.vote_account)
+            .chain(&self.bundled_vote_accounts)
+            .zip(&self.authorized_voters)
+            .find(|(listed_vote_account, _)| *listed_vote_account == vote_account)
+            .map(|(_, authorized_voter)| *authorized_voter)
+    }
+
+    /// Whether the identity rotation sold as a built-in secondary item is still pending.
+    pub fn has_pending_identity_rotation(&self) -> bool {
+        self.secondary_items.iter().any(|item| {
+            item.kind == SecondaryItemKind::ValidatorIdentity && item.date_validated.is_none()
+        })
+    }
+
+    /// Lamports held by the self-stake accounts when they were listed.
+    pub fn self_stake_value(&self) -> u64 {
+        self.self_stake.iter().map(|stake| stake.lamports).sum()
+    }
+
+    /// What the buyer gets back when the secondary items are not all delivered in time: the
+    /// collateral of the items left unsettled, plus the cost of the custom ones. Built-in items
+    /// depend on the buyer alone, so their cost is never refunded.
+    pub fn undelivered_items_refund(&self) -> u64 {
+        self.secondary_items
+            .iter()
+            .filter(|item| item.date_validated.is_none())
+            .map(|item| match item.kind {
+                SecondaryItemKind::Custom => item.cost * 2,
+                SecondaryItemKind::ValidatorIdentity => item.cost,
+            })
+            .sum::<u64>()
+    }
+
+    /// Asserts that mediation was requested over the whole escrow, or over the secondary item at
+    /// `item_index`, and has not been settled yet.
+    pub fn assert_mediation_pending(&self, item_index: Option<u32>) -> ProgramResult {
+        match item_index {
+            Some(item_index) => {
+                let item = self
+                    .secondary_items
+                    .get(item_index as usize)
+                    .error_log("Error @ secondary item index out of bounds")?;
+                match item.request_mediation_date {
+                    Some(_request_mediation_date) => {
+                        if let Some(_date_validated) = item.date_validated {
+                            Err(InglError::TooLate
+                                .utilize("Secondary item has already been settled"))?
+                        }
+                    }
+                    None => Err(InglError::TooEarly
+                        .utilize("Mediation has not been requested for this item"))?,
+                }
+            }
+            None => match self.request_mediation_date {
+                Some(_request_mediation_date) => {
+                    if self.mediation_date.is_some() {
+                        Err(InglError::TooLate.utilize("Mediation has already taken place"))?
+                    }
+                }
+                None => Err(InglError::TooEarly.utilize("Mediation has not been requested yet"))?,
+            },
+        }
+        Ok(())
+    }
+
+    /// Whether a dispute raised against a single secondary item still awaits a mediator.
+    pub fn has_pending_item_dispute(&self) -> bool {
+        self.secondary_items
+            .iter()
+            .any(|item| item.request_mediation_date.is_some() && item.date_validated.is_none())
+    }
+
+    pub fn secondary_items_cost(&self) -> u64 {
+        self.secondary_items
+            .iter()
+            .map(|item| item.cost)
+            .sum::<u64>()
+    }
+}
+
+/// A buyer's negotiated price for a fixed price listing. The offer account holds the offered
+/// lamports along with the secondary items collateral until it is accepted or cancelled.
+#[derive(BorshDeserialize, BorshSerialize, Debug, Validate)]
+#[validation_phrase(crate::state::consts::OFFER_VALIDATION_PHRASE)]
+pub struct Offer {
+    pub validation_phrase: u32,
+    pub buyer: Pubkey,
+    pub vote_account: Pubkey,
+    pub amount: u64,
+    pub collateral: u64,
+    pub date: u32,
+    pub expiry_date: u32,
+}
+
+impl Offer {
+    pub fn get_space() -> usize {
+        4 + 32 + 32 + 8 + 8 + 4 + 4
     }
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Copy, Clone, Debug)]
 pub struct Purchase {
     pub buyer: Pubkey,
+    /// Voter the buyer asked to be assigned along with the authorized withdrawer.
+    pub authorized_voter: Pubkey,
     pub date: u32,
     pub date_finalized: Option<u32>,
+    /// Amount held in the escrow of this purchase, in lamports or in the payment mint.
+    pub escrowed: u64,
+    /// `date_listed` of the listing sold, from which the escrow seed is derived.
+    pub escrow_nonce: u32,
 }
 
 impl Purchase {
     pub fn get_space() -> usize {
-        32 + 4 + 5
+        32 + 32 + 4 + 5 + 8 + 4
+    }
+
+    /// Scopes the escrow of a purchase to the listing it was made on, with its `escrow_nonce`.
+    /// The escrow seeds are `ESCROW_ACCOUNT_SEED`, the vote account and this seed.
+    pub fn escrow_seed(escrow_nonce: u32) -> [u8; 4] {
+        escrow_nonce.to_le_bytes()
+    }
+
+    /// Scopes the claims and mediation cases of a purchase to the secondary item at `item_index`,
+    /// or to the rest of the escrow when `None`. It follows the escrow seed.
+    pub fn item_seed(item_index: Option<u32>) -> [u8; 4] {
+        item_index.unwrap_or(u32::MAX).to_le_bytes()
+    }
+}
+
+/// What each party is owed out of the escrow of a purchase, for one of its secondary items or for
+/// the rest of the escrow once finalized, withdrawn with `Claim`. Lamports are held by this account
+/// itself, while tokens stay in the escrow token account.
+#[derive(BorshDeserialize, BorshSerialize, Debug, Validate)]
+#[validation_phrase(crate::state::consts::CLAIMS_VALIDATION_PHRASE)]
+pub struct Claims {
+    pub validation_phrase: u32,
+    pub vote_account: Pubkey,
+    pub escrow_nonce: u32,
+    pub item_index: Option<u32>,
+    pub payment_mint: Option<Pubkey>,
+    /// Refunded the rent of this account once every claim is withdrawn.
+    pub rent_payer: Pubkey,
+    pub claims: Vec<Claim>,
+}
+
+impl Claims {
+    pub fn get_space(&self) -> usize {
+        4 + 32 + 4 + 5 + 33 + 32 + 4 + Claim::get_space() * self.claims.len()
+    }
+}
+
+/// Quorum of a mediator panel, at least `MIN_MEDIATION_PANEL_THRESHOLD` and at most the number of
+/// active mediators when listed. A dispute settles once `threshold` mediators propose the same
+/// shares, or on the median proposal of at least `threshold` votes once `voting_interval` seconds
+/// passed since the first vote, or of the votes cast another `voting_interval` later.
+#[derive(BorshSerialize, BorshDeserialize, Copy, Clone, Debug)]
+pub struct MediationPanel {
+    pub threshold: u8,
+    pub voting_interval: u32,
+}
+
+impl MediationPanel {
+    pub fn get_space() -> usize {
+        1 + 4
+    }
+}
+
+/// Votes of a mediator panel on a dispute over the escrow of a purchase, or over one of its
+/// secondary items. The account is kept once settled as a record of every vote.
+#[derive(BorshDeserialize, BorshSerialize, Debug, Validate)]
+#[validation_phrase(crate::state::consts::MEDIATION_CASE_VALIDATION_PHRASE)]
+pub struct MediationCase {
+    pub validation_phrase: u32,
+    pub vote_account: Pubkey,
+    pub purchase_date: u32,
+    pub item_index: Option<u32>,
+    pub deadline: u32,
+    pub date_settled: Option<u32>,
+    pub votes: Vec<MediationVote>,
+}
+
+impl MediationCase {
+    pub fn get_space(&self) -> usize {
+        4 + 32 + 4 + 5 + 4 + 5 + 4 + MediationVote::get_space() * self.votes.len()
+    }
+
+    /// Returns the shares proposed by at least `threshold` mediators or, once the deadline has
+    /// passed and at least `threshold` mediators voted, the median proposal ordered by buyer share.
+    /// A panel that does not reach the quorum gets another `voting_interval` after the deadline,
+    /// past which the median of the votes cast applies.
+    pub fn decision(&self, mediation_panel: &MediationPanel, date: u32) -> Option<MediationShares> {
+        let threshold = mediation_panel.threshold;
+        if let Some(vote) = self.votes.iter().find(|vote| {
+            self.votes
+                .iter()
+                .filter(|other| other.mediation_shares == vote.mediation_shares)
+                .count()
+                >= threshold as usize
+        }) {
+            return Some(vote.mediation_shares);
+        }
+        let has_quorum = self.votes.len() >= threshold as usize;
+        let grace_deadline = self
+            .deadline
+            .saturating_add(mediation_panel.voting_interval);
+        if self.votes.is_empty() || date <= self.deadline || (!has_quorum && date <= grace_deadline)
+        {
+            return None;
+        }
+        let mut proposals: Vec<MediationShares> = self
+            .votes
+            .iter()
+            .map(|vote| vote.mediation_shares)
+            .collect();
+        proposals.sort_by_key(|shares| (shares.buyer, shares.seller));
+        Some(proposals[(proposals.len() - 1) / 2])
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Copy, Clone, Debug)]
+pub struct MediationVote {
+    pub mediator: Pubkey,
+    pub mediation_shares: MediationShares,
+    pub date: u32,
+}
+
+impl MediationVote {
+    pub fn get_space() -> usize {
+        32 + MediationShares::get_space() + 4
+    }
+}
+
+/// Mediators allowed to settle disputes, managed by the team with `AddMediator`,
+/// `RemoveMediator` and `SuspendMediator`.
+#[derive(BorshDeserialize, BorshSerialize, Debug, Validate)]
+#[validation_phrase(crate::state::consts::MEDIATOR_REGISTRY_VALIDATION_PHRASE)]
+pub struct MediatorRegistry {
+    pub validation_phrase: u32,
+    pub mediators: Vec<Mediator>,
+}
+
+impl MediatorRegistry {
+    pub fn get_space(&self) -> usize {
+        4 + 4
+            + self
+                .mediators
+                .iter()
+                .map(|mediator| mediator.get_space())
+                .sum::<usize>()
+    }
+
+    /// Whether `key` is a registered mediator that is not suspended.
+    pub fn is_active_mediator(&self, key: &Pubkey) -> bool {
+        self.mediators
+            .iter()
+            .any(|mediator| mediator.key == *key && !mediator.suspended)
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+pub struct Mediator {
+    pub key: Pubkey,
+    pub name: String,
+    /// Where buyers and sellers can reach the mediator, such as a website or a handle.
+    pub contact: String,
+    pub date_added: u32,
+    /// Suspended mediators stay listed but cannot mediate until reinstated.
+    pub suspended: bool,
+}
+
+impl Mediator {
+    pub fn get_space(&self) -> usize {
+        32 + 4 + self.name.len() + 4 + self.contact.len() + 4 + 1
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Copy, Clone, Debug)]
+pub struct Claim {
+    pub claimant: Pubkey,
+    pub amount: u64,
+}
+
+impl Claim {
+    pub fn get_space() -> usize {
+        32 + 8
+    }
+}
+
+/// A repurchase right the seller keeps on a sold validator.
+#[derive(BorshSerialize, BorshDeserialize, Copy, Clone, Debug)]
+pub enum BuybackClause {
+    /// The seller can buy the validator back within `window` seconds of the purchase, paying the
+    /// purchase price plus `premium_basis_points`. The program keeps the authorized withdrawer
+    /// until the window closes.
+    CallOption {
+        window: u32,
+        premium_basis_points: u16,
+    },
+    /// For `duration` seconds after the purchase, a relisting by the buyer is reserved to the
+    /// seller for `response_period` seconds.
+    RightOfFirstRefusal { duration: u32, response_period: u32 },
+}
+
+impl BuybackClause {
+    pub fn get_space() -> usize {
+        1 + 4 + 4
+    }
+
+    /// Number of seconds after the purchase during which the clause can be exercised.
+    pub fn duration(&self) -> u32 {
+        match self {
+            BuybackClause::CallOption { window, .. } => *window,
+            BuybackClause::RightOfFirstRefusal { duration, .. } => *duration,
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Copy, Clone, Debug)]
+pub struct FirstRefusal {
+    pub holder: Pubkey,
+    pub until: u32,
+}
+
+impl FirstRefusal {
+    pub fn get_space() -> usize {
+        32 + 4
+    }
+}
+
+/// The buyback clause of a sold validator. It outlives the listing storage so that a right of
+/// first refusal still applies when the buyer relists.
+#[derive(BorshDeserialize, BorshSerialize, Debug, Validate)]
+#[validation_phrase(crate::state::consts::BUYBACK_VALIDATION_PHRASE)]
+pub struct Buyback {
+    pub validation_phrase: u32,
+    pub vote_account: Pubkey,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub clause: BuybackClause,
+    pub price: u64,
+    pub date: u32,
+}
+
+impl Buyback {
+    pub fn get_space() -> usize {
+        4 + 32 + 32 + 32 + BuybackClause::get_space() + 8 + 4
+    }
+
+    pub fn is_active(&self, date: u32) -> bool {
+        date <= self.date.saturating_add(self.clause.duration())
+    }
+
+    /// Price the seller pays to exercise a call option.
+    pub fn exercise_price(&self) -> u64 {
+        match self.clause {
+            BuybackClause::CallOption {
+                premium_basis_points,
+                ..
+            } => (self.price as u128 * (10000 + premium_basis_points as u128) / 10000) as u64,
+            BuybackClause::RightOfFirstRefusal { .. } => self.price,
+        }
     }
 }
 
+/// Lets a buyer pay `Storage.authorized_withdrawer_cost` as a down payment followed by
+/// `installment_count` equal installments, one due every `installment_interval` after the
+/// purchase. The program keeps the authorized withdrawer until the last one is paid.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug)]
+pub struct InstallmentPlan {
+    pub down_payment: u64,
+    pub installment_count: u8,
+    pub installment_interval: u32,
+    pub grace_period: u32,
+    pub installments_paid: u8,
+}
+
+impl InstallmentPlan {
+    pub fn get_space() -> usize {
+        8 + 1 + 4 + 4 + 1
+    }
+
+    pub fn is_paid_in_full(&self) -> bool {
+        self.installments_paid >= self.installment_count
+    }
+
+    /// Amount of the next installment. The last one also covers the rounding remainder.
+    pub fn next_installment_amount(&self, price: u64) -> u64 {
+        let remaining = price.saturating_sub(self.down_payment);
+        let installment = remaining / self.installment_count.max(1) as u64;
+        if self.installments_paid + 1 >= self.installment_count {
+            remaining - installment * (self.installment_count.max(1) as u64 - 1)
+        } else {
+            installment
+        }
+    }
+
+    pub fn next_due_date(&self, purchase_date: u32) -> u32 {
+        purchase_date.saturating_add(
+            self.installment_interval
+                .saturating_mul(self.installments_paid as u32 + 1),
+        )
+    }
+
+    /// The listing can be forfeited once the next installment is overdue by the grace period.
+    pub fn is_defaulted(&self, purchase_date: u32, date: u32) -> bool {
+        !self.is_paid_in_full()
+            && date
+                > self
+                    .next_due_date(purchase_date)
+                    .saturating_add(self.grace_period)
+    }
+}
+
+/// Sells `basis_points` of the future vote rewards for `Storage.authorized_withdrawer_cost`
+/// instead of the authorized withdrawer itself. The program keeps the withdrawer and
+/// `withdraw_rewards` pays each holder its recorded share.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug)]
+pub struct RevenueShare {
+    pub basis_points: u16,
+    pub holders: Vec<RevenueShareHolder>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug)]
+pub struct RevenueShareHolder {
+    pub holder: Pubkey,
+    pub basis_points: u16,
+}
+
+impl RevenueShare {
+    pub fn get_space(&self) -> usize {
+        2 + 4 + self.holders.len() * (32 + 2)
+    }
+
+    pub fn sold_basis_points(&self) -> u16 {
+        self.holders.iter().map(|holder| holder.basis_points).sum()
+    }
+
+    /// Amounts owed to each holder, in order, out of a withdrawal of `lamports`.
+    /// The seller keeps the remainder.
+    pub fn split(&self, lamports: u64) -> Vec<u64> {
+        self.holders
+            .iter()
+            .map(|holder| (lamports as u128 * holder.basis_points as u128 / 10000) as u64)
+            .collect()
+    }
+}
+
+/// How the authorized withdrawer of a listing is sold.
+/// For auctions, `Storage.authorized_withdrawer_cost` holds the starting price, and for leases
+/// the price of the whole lease.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug)]
+pub enum StoredListingType {
+    FixedPrice,
+    EnglishAuction(EnglishAuction),
+    DutchAuction(DutchAuction),
+    Lease(Lease),
+}
+
+impl StoredListingType {
+    pub fn get_space() -> usize {
+        1 + EnglishAuction::get_space()
+            .max(DutchAuction::get_space())
+            .max(Lease::get_space())
+    }
+}
+
+/// Rents out the vote rewards for `epochs` epochs. The program keeps the authorized withdrawer
+/// for the whole lease and hands it back to the lessor once the lease ends.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug)]
+pub struct Lease {
+    pub epochs: u64,
+    pub lessee: Option<Pubkey>,
+    pub start_epoch: u64,
+}
+
+impl Lease {
+    pub fn get_space() -> usize {
+        8 + 33 + 8
+    }
+
+    pub fn end_epoch(&self) -> u64 {
+        self.start_epoch.saturating_add(self.epochs)
+    }
+
+    /// The lessee is entitled to the rewards withdrawn after `start_epoch` and through
+    /// `end_epoch`, since the rewards of an epoch are only credited once the next one starts.
+    pub fn is_active(&self, epoch: u64) -> bool {
+        self.lessee.is_some() && self.start_epoch < epoch && epoch <= self.end_epoch()
+    }
+
+    pub fn has_ended(&self, epoch: u64) -> bool {
+        self.lessee.is_some() && epoch > self.end_epoch()
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug)]
+pub struct EnglishAuction {
+    pub min_bid_increment: u64,
+    pub end_date: u32,
+    pub reserve_price: Option<u64>,
+    pub highest_bid: Option<Bid>,
+}
+
+impl EnglishAuction {
+    pub fn get_space() -> usize {
+        8 + 4 + 9 + 1 + Bid::get_space()
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Copy, Clone, Debug)]
+pub enum PriceSchedule {
+    Linear,
+    Stepwise { step_interval: u32 },
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug)]
+pub struct DutchAuction {
+    pub floor_price: u64,
+    pub start_date: u32,
+    pub end_date: u32,
+    pub schedule: PriceSchedule,
+}
+
+impl DutchAuction {
+    pub fn get_space() -> usize {
+        8 + 4 + 4 + 1 + 4
+    }
+
+    /// Price at `date`, decaying from `start_price` at `start_date` to `floor_price` at `end_date`.
+    pub fn current_price(&self, start_price: u64, date: u32) -> u64 {
+        if date <= self.start_date || start_price <= self.floor_price {
+            return start_price;
+        }
+        if date >= self.end_date {
+            return self.floor_price;
+        }
+        let duration = (self.end_date - self.start_date) as u128;
+        let elapsed = match self.schedule {
+            PriceSchedule::Linear => (date - self.start_date) as u128,
+            PriceSchedule::Stepwise { step_interval } => {
+                let step_interval = step_interval.max(1);
+                ((date - self.start_date) / step_interval * step_interval) as u128
+            }
+        };
+        let decay = (start_price - self.floor_price) as u128 * elapsed / duration;
+        start_price - decay as u64
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Copy, Clone, Debug)]
+pub struct Bid {
+    pub bidder: Pubkey,
+    pub amount: u64,
+    pub date: u32,
+}
+
+impl Bid {
+    pub fn get_space() -> usize {
+        32 + 8 + 4
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Copy, Clone, Debug)]
+pub struct SelfStake {
+    pub stake_account: Pubkey,
+    /// Balance of the stake account when it was listed.
+    pub lamports: u64,
+}
+
+impl SelfStake {
+    pub fn get_space() -> usize {
+        32 + 8
+    }
+}
+
+/// Secondary items are described in free text and validated by the buyer, except for built-in
+/// kinds which the program verifies on-chain.
+#[derive(BorshSerialize, BorshDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SecondaryItemKind {
+    Custom,
+    /// Rotation of `VoteState.node_pubkey` to an identity chosen by the buyer, validated by
+    /// `rotate_validator_identity`.
+    ValidatorIdentity,
+}
+
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct StoredSecondaryItem {
     pub cost: u64,
     pub name: String,
     pub description: String,
+    pub kind: SecondaryItemKind,
     pub date_validated: Option<u32>,
+    /// Set when the buyer or the seller disputes the delivery of this item alone.
+    pub request_mediation_date: Option<u32>,
+    /// Split of the item cost and collateral decided by a mediator. A mediated item counts as
+    /// validated.
+    pub mediation_shares: Option<MediationShares>,
 }
 
 impl StoredSecondaryItem {
     pub fn get_space(&self) -> usize {
-        8 + self.name.len() + 4 + self.description.len() + 4 + 5
+        8 + self.name.len()
+            + 4
+            + self.description.len()
+            + 4
+            + 1
+            + 5
+            + 5
+            + 1
+            + MediationShares::get_space()
+    }
+
+    /// Buyer, seller and team shares of the item cost and of the buyer's collateral for it. Unless
+    /// a mediator split them, the collateral goes back to the buyer and the cost to the seller.
+    pub fn settlement(&self) -> Result<(u64, u64, u64), ProgramError> {
+        match &self.mediation_shares {
+            Some(mediation_shares) => mediation_shares.split(
+                self.cost
+                    .checked_mul(2)
+                    .error_log("item cost * 2 overflows")?,
+            ),
+            None => Ok((self.cost, self.cost, 0)),
+        }
     }
 }
 
@@ -120,7 +842,7 @@ pub enum LogColors {
     Blank,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
 pub struct MediationShares {
     pub buyer: u8,
     pub seller: u8,
@@ -134,6 +856,29 @@ impl MediationShares {
         }
         Ok(())
     }
+
+    /// Returns the buyer, seller and team shares of `amount`. Rounding leftovers go to the team.
+    pub fn split(&self, amount: u64) -> Result<(u64, u64, u64), ProgramError> {
+        let to_buyer = amount
+            .checked_mul(self.buyer as u64)
+            .error_log("buyer share * amount overflows")?
+            .checked_div(100)
+            .error_log("buyer share * amount overflows / 100")?;
+        let to_seller = amount
+            .checked_mul(self.seller as u64)
+            .error_log("seller share * amount overflows")?
+            .checked_div(100)
+            .error_log("seller share * amount overflows / 100")?;
+        let to_team = amount
+            .checked_sub(
+                to_buyer
+                    .checked_add(to_seller)
+                    .error_log("to_buyer + to_seller overflows")?,
+            )
+            .error_log("amount - (to_buyer + to_seller) overflows")?;
+        Ok((to_buyer, to_seller, to_team))
+    }
+
     pub fn get_space() -> usize {
         8 + 8 + 8
     }
@@ -187,6 +932,127 @@ impl AuthorizedVoters {
     pub fn last(&self) -> Option<(&u64, &Pubkey)> {
         self.authorized_voters.iter().next_back()
     }
+
+    /// Voter in charge at `epoch`.
+    pub fn get_authorized_voter(&self, epoch: Epoch) -> Option<Pubkey> {
+        self.authorized_voters
+            .range(..=epoch)
+            .next_back()
+            .map(|(_, voter)| *voter)
+    }
+
+    /// Reads the authorized voters straight out of the bincode encoded vote account data,
+    /// skipping over the votes that precede them.
+    pub fn from_vote_account_data(data: &[u8]) -> Option<Self> {
+        let read_u64 = |offset: usize| {
+            Some(u64::from_le_bytes(
+                data.get(offset..offset + 8)?.try_into().ok()?,
+            ))
+        };
+        let read_pubkey = |offset: usize| Pubkey::try_from(data.get(offset..offset + 32)?).ok();
+
+        let lockout_size = match u32::from_le_bytes(data.get(0..4)?.try_into().ok()?) {
+            0 => return Some(Self::new(read_u64(68)?, read_pubkey(36)?)),
+            // V1_14_11 stores plain lockouts and Current stores them with a latency byte.
+            1 => 8 + 4,
+            2 => 1 + 8 + 4,
+            _ => return None,
+        };
+        let votes_offset = 4 + 32 + 32 + 1;
+        let mut offset = votes_offset + 8 + read_u64(votes_offset)? as usize * lockout_size;
+        offset += match data.get(offset)? {
+            0 => 1,
+            _ => 1 + 8,
+        };
+
+        let voters_count = read_u64(offset)?;
+        offset += 8;
+        let mut authorized_voters = BTreeMap::new();
+        for _ in 0..voters_count {
+            authorized_voters.insert(read_u64(offset)?, read_pubkey(offset + 8)?);
+            offset += 8 + 32;
+        }
+        Some(Self { authorized_voters })
+    }
+}
+
+/// Validator info published in a config program account by `solana validator-info publish`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ValidatorInfo {
+    pub identity: Pubkey,
+    pub name: String,
+}
+impl ValidatorInfo {
+    /// Reads the config keys, which are the validator info id followed by the identity as signer,
+    /// then the name out of the bincode encoded JSON info.
+    pub fn from_config_account_data(data: &[u8]) -> Option<Self> {
+        let read_key = |offset: usize| {
+            Some((
+                Pubkey::try_from(data.get(offset..offset + 32)?).ok()?,
+                *data.get(offset + 32)? != 0,
+            ))
+        };
+        if *data.first()? != 2 || read_key(1)? != (consts::VALIDATOR_INFO_ID, false) {
+            return None;
+        }
+        let (identity, is_signer) = read_key(1 + 33)?;
+        if !is_signer {
+            return None;
+        }
+
+        let info_offset = 1 + 2 * 33 + 8;
+        let info_len = u64::from_le_bytes(data.get(info_offset - 8..info_offset)?.try_into().ok()?);
+        let info_end = info_offset.checked_add(usize::try_from(info_len).ok()?)?;
+        let info = std::str::from_utf8(data.get(info_offset..info_end)?).ok()?;
+        Some(Self {
+            identity,
+            name: json_string_field(info, "name")?,
+        })
+    }
+}
+
+/// Returns the string value of `field` in a flat JSON object.
+fn json_string_field(json: &str, field: &str) -> Option<String> {
+    let mut chars = json.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '"' {
+            continue;
+        }
+        let key = read_json_string(&mut chars)?;
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.next_if_eq(&':').is_none() {
+            continue;
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if key == field {
+            chars.next_if_eq(&'"')?;
+            return read_json_string(&mut chars);
+        }
+    }
+    None
+}
+
+/// Reads a JSON string whose opening quote was already consumed.
+fn read_json_string(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<String> {
+    let mut string = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(string),
+            '\\' => string.push(match chars.next()? {
+                'b' => '\u{8}',
+                'f' => '\u{c}',
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                'u' => {
+                    let code: String = chars.by_ref().take(4).collect();
+                    char::from_u32(u32::from_str_radix(&code, 16).ok()?)?
+                }
+                escaped => escaped,
+            }),
+            c => string.push(c),
+        }
+    }
 }
 
 #[derive(Default, BorshDeserialize, Debug, PartialEq, Eq, Copy, Clone)]
@@ -291,4 +1157,222 @@ pub mod tests {
     fn test_escrow_and_team_fee() {
         assert!(consts::ESCROWED_BASIS_POINTS + consts::TEAM_FEES_BASIS_POINTS <= 10000)
     }
+
+    #[test]
+    fn test_dutch_auction_price() {
+        let mut auction = DutchAuction {
+            floor_price: 100,
+            start_date: 1_000,
+            end_date: 2_000,
+            schedule: PriceSchedule::Linear,
+        };
+        assert_eq!(auction.current_price(1_100, 900), 1_100);
+        assert_eq!(auction.current_price(1_100, 1_500), 600);
+        assert_eq!(auction.current_price(1_100, 2_500), 100);
+
+        auction.schedule = PriceSchedule::Stepwise { step_interval: 300 };
+        assert_eq!(auction.current_price(1_100, 1_299), 1_100);
+        assert_eq!(auction.current_price(1_100, 1_650), 500);
+        assert_eq!(auction.current_price(1_100, 2_000), 100);
+    }
+
+    #[test]
+    fn test_installment_plan() {
+        let mut plan = InstallmentPlan {
+            down_payment: 100,
+            installment_count: 3,
+            installment_interval: 1_000,
+            grace_period: 500,
+            installments_paid: 0,
+        };
+        assert_eq!(plan.next_installment_amount(1_100), 333);
+        assert_eq!(plan.next_due_date(10_000), 11_000);
+        assert!(!plan.is_defaulted(10_000, 11_500));
+        assert!(plan.is_defaulted(10_000, 11_501));
+
+        plan.installments_paid = 2;
+        assert_eq!(plan.next_installment_amount(1_100), 334);
+        assert_eq!(plan.next_due_date(10_000), 13_000);
+
+        plan.installments_paid = 3;
+        assert!(plan.is_paid_in_full());
+        assert!(!plan.is_defaulted(10_000, 20_000));
+    }
+
+    #[test]
+    fn test_secondary_item_settlement() {
+        let mut item = StoredSecondaryItem {
+            cost: 1_000,
+            name: String::from("server"),
+            description: String::new(),
+            kind: SecondaryItemKind::Custom,
+            date_validated: None,
+            request_mediation_date: None,
+            mediation_shares: None,
+        };
+        assert_eq!(item.settlement().unwrap(), (1_000, 1_000, 0));
+
+        item.mediation_shares = Some(MediationShares {
+            buyer: 75,
+            seller: 20,
+            team: 5,
+        });
+        assert_eq!(item.settlement().unwrap(), (1_500, 400, 100));
+    }
+
+    #[test]
+    fn test_mediation_case_decision() {
+        let vote = |buyer: u8, seller: u8| MediationVote {
+            mediator: Pubkey::new_unique(),
+            mediation_shares: MediationShares {
+                buyer,
+                seller,
+                team: 100 - buyer - seller,
+            },
+            date: 1_000,
+        };
+        let panel = |threshold: u8| MediationPanel {
+            threshold,
+            voting_interval: 1_000,
+        };
+        let mut mediation_case = MediationCase {
+            validation_phrase: consts::MEDIATION_CASE_VALIDATION_PHRASE,
+            vote_account: Pubkey::new_unique(),
+            purchase_date: 500,
+            item_index: None,
+            deadline: 2_000,
+            date_settled: None,
+            votes: vec![vote(80, 20), vote(10, 90)],
+        };
+        assert_eq!(mediation_case.decision(&panel(2), 1_500), None);
+
+        mediation_case.votes.push(vote(80, 20));
+        assert_eq!(
+            mediation_case
+                .decision(&panel(2), 1_500)
+                .map(|shares| shares.buyer),
+            Some(80)
+        );
+
+        mediation_case.votes.push(vote(50, 45));
+        assert_eq!(mediation_case.decision(&panel(3), 1_500), None);
+        assert_eq!(mediation_case.decision(&panel(5), 3_000), None);
+        assert_eq!(
+            mediation_case
+                .decision(&panel(5), 3_001)
+                .map(|shares| shares.buyer),
+            Some(50)
+        );
+        assert_eq!(
+            mediation_case
+                .decision(&panel(3), 2_500)
+                .map(|shares| shares.buyer),
+            Some(50)
+        );
+    }
+
+    #[test]
+    fn test_lease_window() {
+        let mut lease = Lease {
+            epochs: 10,
+            lessee: None,
+            start_epoch: 0,
+        };
+        assert!(!lease.is_active(0));
+        assert!(!lease.has_ended(100));
+
+        lease.lessee = Some(Pubkey::new_unique());
+        lease.start_epoch = 400;
+        assert!(!lease.is_active(400));
+        assert!(!lease.has_ended(400));
+        assert!(lease.is_active(401));
+        assert!(lease.is_active(410));
+        assert!(!lease.has_ended(410));
+        assert!(!lease.is_active(411));
+        assert!(lease.has_ended(411));
+    }
+
+    #[test]
+    fn test_buyback_call_option() {
+        let buyback = Buyback {
+            validation_phrase: consts::BUYBACK_VALIDATION_PHRASE,
+            vote_account: Pubkey::new_unique(),
+            seller: Pubkey::new_unique(),
+            buyer: Pubkey::new_unique(),
+            clause: BuybackClause::CallOption {
+                window: 1_000,
+                premium_basis_points: 1500,
+            },
+            price: 2_000,
+            date: 10_000,
+        };
+        assert_eq!(buyback.exercise_price(), 2_300);
+        assert!(buyback.is_active(11_000));
+        assert!(!buyback.is_active(11_001));
+    }
+
+    #[test]
+    fn test_authorized_voters_from_vote_account_data() {
+        let (voter_a, voter_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut data = 2u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&[1; 32 + 32 + 1]);
+        data.extend_from_slice(&2u64.to_le_bytes());
+        data.extend_from_slice(&[7; 2 * 13]);
+        data.push(1);
+        data.extend_from_slice(&42u64.to_le_bytes());
+        data.extend_from_slice(&2u64.to_le_bytes());
+        data.extend_from_slice(&10u64.to_le_bytes());
+        data.extend_from_slice(voter_a.as_ref());
+        data.extend_from_slice(&12u64.to_le_bytes());
+        data.extend_from_slice(voter_b.as_ref());
+
+        let authorized_voters = AuthorizedVoters::from_vote_account_data(&data).unwrap();
+        assert_eq!(authorized_voters.get_authorized_voter(9), None);
+        assert_eq!(authorized_voters.get_authorized_voter(11), Some(voter_a));
+        assert_eq!(authorized_voters.get_authorized_voter(12), Some(voter_b));
+    }
+
+    #[test]
+    fn test_revenue_share_split() {
+        let revenue_share = RevenueShare {
+            basis_points: 5000,
+            holders: vec![
+                RevenueShareHolder {
+                    holder: Pubkey::new_unique(),
+                    basis_points: 2500,
+                },
+                RevenueShareHolder {
+                    holder: Pubkey::new_unique(),
+                    basis_points: 1000,
+                },
+            ],
+        };
+        assert_eq!(revenue_share.sold_basis_points(), 3500);
+        assert_eq!(revenue_share.split(1_000_003), vec![250_000, 100_000]);
+    }
+
+    #[test]
+    fn test_validator_info_from_config_account_data() {
+        let identity = Pubkey::new_unique();
+        let info =
+            r#"{"keybaseUsername":"name","name":"Ingl \"Labs\" \u00e9","website":"ingl.io"}"#;
+        let mut data = vec![2];
+        data.extend_from_slice(consts::VALIDATOR_INFO_ID.as_ref());
+        data.push(0);
+        data.extend_from_slice(identity.as_ref());
+        data.push(1);
+        data.extend_from_slice(&(info.len() as u64).to_le_bytes());
+        data.extend_from_slice(info.as_bytes());
+
+        assert_eq!(
+            ValidatorInfo::from_config_account_data(&data),
+            Some(ValidatorInfo {
+                identity,
+                name: "Ingl \"Labs\" \u{e9}".to_string(),
+            })
+        );
+        assert_eq!(ValidatorInfo::from_config_account_data(&data[..80]), None);
+        data[1 + 33 + 32] = 0;
+        assert_eq!(ValidatorInfo::from_config_account_data(&data), None);
+    }
 }
@@ -1,10 +1,20 @@
 use solana_program::{
     account_info::AccountInfo,
     entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
     program_error::ProgramError,
     pubkey::Pubkey,
+    system_instruction,
     sysvar::{self, clock::Clock, rent::Rent, Sysvar},
 };
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent,
+};
+use spl_token_2022::{
+    extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions},
+    state::{Account as TokenAccount, Mint},
+};
 use std::slice::Iter;
 
 use crate::{colored_log, error::InglError, state::LogColors::*};
@@ -60,6 +70,233 @@ impl AccountInfoHelpers for AccountInfo<'_> {
     }
 }
 
+/// Creates the program owned PDA `account` with `space` bytes, paid for by `payer`. A PDA that
+/// was already sent lamports can't be created with `create_account`, so it is topped up to the
+/// rent exempt minimum, allocated and assigned instead.
+pub fn create_pda_account<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    space: usize,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    create_pda_account_with_rent(program_id, payer, account, space, seeds, &Rent::get()?)
+}
+
+/// `create_pda_account`, with the rent read by the caller.
+pub fn create_pda_account_with_rent<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    space: usize,
+    seeds: &[&[u8]],
+    rent_data: &Rent,
+) -> ProgramResult {
+    let required_lamports = rent_data.minimum_balance(space);
+    if account.lamports() == 0 {
+        return invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                account.key,
+                required_lamports,
+                space as u64,
+                program_id,
+            ),
+            &[payer.clone(), account.clone()],
+            &[seeds],
+        )
+        .error_log("Error @ system_instruction::create_account");
+    }
+
+    if required_lamports > account.lamports() {
+        invoke(
+            &system_instruction::transfer(
+                payer.key,
+                account.key,
+                required_lamports - account.lamports(),
+            ),
+            &[payer.clone(), account.clone()],
+        )
+        .error_log("Error @ system_instruction::transfer")?;
+    }
+    invoke_signed(
+        &system_instruction::allocate(account.key, space as u64),
+        std::slice::from_ref(account),
+        &[seeds],
+    )
+    .error_log("Error @ system_instruction::allocate")?;
+    invoke_signed(
+        &system_instruction::assign(account.key, program_id),
+        std::slice::from_ref(account),
+        &[seeds],
+    )
+    .error_log("Error @ system_instruction::assign")?;
+    Ok(())
+}
+
+/// Moves lamports out of an account owned by this program.
+pub fn move_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> ProgramResult {
+    let from_starting_lamports = from.lamports();
+    **from.lamports.borrow_mut() = from_starting_lamports
+        .checked_sub(amount)
+        .error_log("Error subtracting lamports from source account")?;
+    let to_starting_lamports = to.lamports();
+    **to.lamports.borrow_mut() = to_starting_lamports
+        .checked_add(amount)
+        .error_log("Error adding lamports to destination account")?;
+    Ok(())
+}
+
+/// Asserts that `token_account` is a token account of `mint` owned by `owner`.
+pub fn assert_token_account(
+    token_account: &AccountInfo,
+    token_program: &Pubkey,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> ProgramResult {
+    token_account
+        .assert_owner(token_program)
+        .error_log("Error @ token account program owner assertion")?;
+    let token_account_data = token_account.data.borrow();
+    let token_account_state = StateWithExtensions::<TokenAccount>::unpack(&token_account_data)
+        .error_log("Error @ token account unpack")?;
+    token_account_state
+        .base
+        .mint
+        .assert_match(mint)
+        .error_log("Error @ token account mint assertion")?;
+    token_account_state
+        .base
+        .owner
+        .assert_match(owner)
+        .error_log("Error @ token account owner assertion")
+}
+
+/// Asserts that `token_program` is spl-token or Token-2022 and owns `mint`.
+pub fn assert_token_program_and_mint(
+    token_program: &AccountInfo,
+    mint: &AccountInfo,
+    expected_mint: &Pubkey,
+) -> ProgramResult {
+    spl_token_2022::check_spl_token_program_account(token_program.key)
+        .error_log("Error @ token program assertion")?;
+    mint.assert_key_match(expected_mint)
+        .error_log("Error @ mint assertion")?;
+    mint.assert_owner(token_program.key)
+        .error_log("Error @ mint owner assertion")
+}
+
+/// Creates the associated token account of the escrow PDA if it does not exist yet, paid for by
+/// `payer`.
+pub fn create_escrow_token_account<'a>(
+    payer: &AccountInfo<'a>,
+    escrow_token_account: &AccountInfo<'a>,
+    escrow_account: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    associated_token_program: &AccountInfo<'a>,
+) -> ProgramResult {
+    associated_token_program
+        .assert_key_match(&spl_associated_token_account::id())
+        .error_log("Error @ associated token program assertion")?;
+    invoke(
+        &create_associated_token_account_idempotent(
+            payer.key,
+            escrow_account.key,
+            mint.key,
+            token_program.key,
+        ),
+        &[
+            payer.clone(),
+            escrow_token_account.clone(),
+            escrow_account.clone(),
+            mint.clone(),
+            system_program.clone(),
+            token_program.clone(),
+            associated_token_program.clone(),
+        ],
+    )
+    .error_log("Error @ create_associated_token_account_idempotent")
+}
+
+/// Asserts that `escrow_token_account` is the associated token account of the escrow PDA.
+pub fn assert_escrow_token_account(
+    escrow_token_account: &AccountInfo,
+    escrow_account: &AccountInfo,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> ProgramResult {
+    escrow_token_account
+        .assert_key_match(&get_associated_token_address_with_program_id(
+            escrow_account.key,
+            mint,
+            token_program,
+        ))
+        .error_log("Error @ escrow token account assertion")
+}
+
+pub fn get_token_balance(token_account: &AccountInfo) -> Result<u64, ProgramError> {
+    let token_account_data = token_account.data.borrow();
+    Ok(
+        StateWithExtensions::<TokenAccount>::unpack(&token_account_data)
+            .error_log("Error @ token account unpack")?
+            .base
+            .amount,
+    )
+}
+
+/// Returns the Token-2022 transfer fee to add on top of `amount` for the recipient to receive
+/// all of it. Mints without a transfer fee extension charge nothing.
+pub fn get_inverse_transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64, ProgramError> {
+    let mint_data = mint.data.borrow();
+    let mint_state =
+        StateWithExtensions::<Mint>::unpack(&mint_data).error_log("Error @ mint unpack")?;
+    match mint_state.get_extension::<TransferFeeConfig>() {
+        Ok(transfer_fee_config) => transfer_fee_config
+            .calculate_inverse_epoch_fee(Clock::get()?.epoch, amount)
+            .error_log("Error @ transfer fee calculation"),
+        Err(_) => Ok(0),
+    }
+}
+
+/// Transfers tokens with `transfer_checked`, which both spl-token and Token-2022 accept.
+/// `signer_seeds` must sign for `authority` when it is a PDA.
+pub fn transfer_tokens<'a>(
+    token_program: &AccountInfo<'a>,
+    source: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let decimals = StateWithExtensions::<Mint>::unpack(&mint.data.borrow())
+        .error_log("Error @ mint unpack")?
+        .base
+        .decimals;
+    invoke_signed(
+        &spl_token_2022::instruction::transfer_checked(
+            token_program.key,
+            source.key,
+            mint.key,
+            destination.key,
+            authority.key,
+            &[],
+            amount,
+            decimals,
+        )?,
+        &[
+            source.clone(),
+            mint.clone(),
+            destination.clone(),
+            authority.clone(),
+        ],
+        signer_seeds,
+    )
+    .error_log("Error @ token transfer")
+}
+
 /// Get clock_data
 pub fn get_clock_data(
     iter: &mut Iter<AccountInfo>,